@@ -0,0 +1,47 @@
+//! Single-threaded benchmarks, measuring the overhead of enabling blocking.
+//!
+//! If blocking is enabled for one side, the other side has to check after each operation
+//! whether it has to wake up a waiting thread (or task).
+//! In these benchmarks, nobody is ever waiting.
+
+use criterion::{black_box, criterion_group, criterion_main};
+
+use rtrb::{Blocking, Consumer, Producer, RingBuffer};
+
+fn add_function<M>(
+    group: &mut criterion::BenchmarkGroup<M>,
+    id: &str,
+    (mut p, mut c): (Producer<u8>, Consumer<u8>),
+) where
+    M: criterion::measurement::Measurement,
+{
+    group.bench_function(id, |b| {
+        let mut i: u8 = 0;
+        b.iter(|| {
+            p.push(black_box(i)).unwrap();
+            assert_eq!(c.pop(), Ok(i));
+            i = i.wrapping_add(1);
+        });
+    });
+}
+
+pub fn criterion_benchmark(criterion: &mut criterion::Criterion) {
+    let mut group = criterion.benchmark_group("blocking");
+
+    add_function(&mut group, "1-non-blocking", RingBuffer::new(16));
+    add_function(
+        &mut group,
+        "2-blocking-consumer",
+        RingBuffer::with_blocking(16, Blocking::Consumer),
+    );
+    add_function(
+        &mut group,
+        "3-blocking-both",
+        RingBuffer::with_blocking(16, Blocking::Both),
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Single-threaded benchmarks, comparing queues with and without mask-based indexing.
+//!
+//! The baseline is a queue created with `RingBuffer::new()` with a capacity
+//! that is not a power of two.
+//! The other two queues have the same capacity (a power of two),
+//! but only one of them is created with `RingBuffer::new_power_of_two()`.
+
+use criterion::{black_box, criterion_group, criterion_main};
+
+use rtrb::{Consumer, Handle, Producer, RingBuffer};
+
+const CAPACITY: usize = 1024;
+
+fn add_functions<M, B>(
+    group: &mut criterion::BenchmarkGroup<M>,
+    prefix: &str,
+    (mut p, mut c): (Producer<u8, B>, Consumer<u8, B>),
+) where
+    M: criterion::measurement::Measurement,
+    B: Handle<u8>,
+{
+    // Pushing and popping one item at a time wraps around frequently.
+    group.bench_function(format!("{}-push-pop", prefix), |b| {
+        let mut i: u8 = 0;
+        b.iter(|| {
+            p.push(black_box(i)).unwrap();
+            assert_eq!(c.pop(), Ok(i));
+            i = i.wrapping_add(1);
+        });
+    });
+
+    // Filling the queue halfway and then draining it.
+    group.bench_function(format!("{}-fill-drain", prefix), |b| {
+        b.iter(|| {
+            for i in 0..CAPACITY / 2 {
+                p.push(black_box(i as u8)).unwrap();
+            }
+            for i in 0..CAPACITY / 2 {
+                assert_eq!(c.pop(), Ok(i as u8));
+            }
+        });
+    });
+
+    group.bench_function(format!("{}-slots", prefix), |b| {
+        b.iter(|| black_box(p.slots()) + black_box(c.slots()));
+    });
+}
+
+pub fn criterion_benchmark(criterion: &mut criterion::Criterion) {
+    let mut group = criterion.benchmark_group("power-of-two");
+
+    add_functions(&mut group, "1-baseline", RingBuffer::new(CAPACITY - 1));
+    add_functions(&mut group, "2-compare", RingBuffer::new(CAPACITY));
+    add_functions(&mut group, "3-mask", RingBuffer::new_power_of_two(CAPACITY));
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
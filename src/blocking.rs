@@ -0,0 +1,567 @@
+//! Blocking variants of the reading and writing functions.
+//!
+//! Blocking has to be enabled with [`RingBuffer::with_blocking()`].
+
+use std::sync::Arc;
+use std::task::{Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use crate::chunks::{ChunkError, ChunkTimeoutError, ReadChunk, WriteChunk, WriteChunkUninit};
+use crate::waker::AtomicWaker;
+use crate::{Consumer, PopError, PopTimeoutError, Producer, PushError, PushTimeoutError};
+
+// This is used in the documentation.
+#[allow(unused_imports)]
+use crate::RingBuffer;
+
+impl<T> Producer<T> {
+    /// Pushes an element into the queue, blocking while the queue is full.
+    ///
+    /// The calling thread is put to sleep until the [`Consumer`] makes a slot available.
+    ///
+    /// # Errors
+    ///
+    /// If the queue has been closed or the [`Consumer`] has been dropped
+    /// (i.e. no slot will ever become available), the element is returned back as an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::{Blocking, RingBuffer};
+    ///
+    /// let (mut p, mut c) = RingBuffer::with_blocking(1, Blocking::Producer);
+    ///
+    /// let producer_thread = std::thread::spawn(move || {
+    ///     for i in 0..10 {
+    ///         p.push_blocking(i).unwrap();
+    ///     }
+    /// });
+    ///
+    /// for i in 0..10 {
+    ///     loop {
+    ///         if let Ok(value) = c.pop() {
+    ///             assert_eq!(value, i);
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    /// producer_thread.join().unwrap();
+    /// ```
+    pub fn push_blocking(&mut self, value: T) -> Result<(), PushError<T>> {
+        self.assert_blocking();
+        wait(&self.buffer.producer_waker, None, || self.push_ready());
+        self.push(value)
+    }
+
+    /// Pushes an element into the queue, blocking while the queue is full,
+    /// but at most for the given `timeout`.
+    ///
+    /// See [`Producer::push_blocking()`].
+    ///
+    /// # Errors
+    ///
+    /// If the queue is still full after `timeout` has elapsed,
+    /// the element is returned back in [`PushTimeoutError::Timeout`].
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// the element is returned back in [`PushTimeoutError::Closed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rtrb::{Blocking, PushTimeoutError, RingBuffer};
+    ///
+    /// let (mut p, c) = RingBuffer::with_blocking(1, Blocking::Producer);
+    ///
+    /// assert_eq!(p.push_timeout(10, Duration::from_millis(1)), Ok(()));
+    /// assert_eq!(
+    ///     p.push_timeout(20, Duration::from_millis(1)),
+    ///     Err(PushTimeoutError::Timeout(20))
+    /// );
+    /// ```
+    pub fn push_timeout(&mut self, value: T, timeout: Duration) -> Result<(), PushTimeoutError<T>> {
+        self.push_until(value, deadline_from(timeout))
+    }
+
+    /// Pushes an element into the queue, blocking while the queue is full,
+    /// but at most until the given `deadline`.
+    ///
+    /// See [`Producer::push_timeout()`].
+    ///
+    /// # Errors
+    ///
+    /// If the queue is still full when `deadline` is reached,
+    /// the element is returned back in [`PushTimeoutError::Timeout`].
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// the element is returned back in [`PushTimeoutError::Closed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn push_deadline(
+        &mut self,
+        value: T,
+        deadline: Instant,
+    ) -> Result<(), PushTimeoutError<T>> {
+        self.push_until(value, Some(deadline))
+    }
+
+    /// Returns `n` slots (initially containing their [`Default`] value) for writing,
+    /// blocking until enough slots are available.
+    ///
+    /// See [`Producer::write_chunk()`].
+    ///
+    /// # Errors
+    ///
+    /// If `n` is larger than the capacity of the queue, an error
+    /// (containing the number of available slots) is returned.
+    /// If the queue has been closed or the [`Consumer`] has been dropped
+    /// (i.e. no more slots will become available), [`ChunkError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_blocking(&mut self, n: usize) -> Result<WriteChunk<'_, T>, ChunkError>
+    where
+        T: Default,
+    {
+        self.write_chunk_uninit_blocking(n).map(WriteChunk::from)
+    }
+
+    /// Returns `n` slots (initially containing their [`Default`] value) for writing,
+    /// blocking until enough slots are available, but at most for the given `timeout`.
+    ///
+    /// See [`Producer::write_chunk()`].
+    ///
+    /// # Errors
+    ///
+    /// If not enough slots are available after `timeout` has elapsed,
+    /// [`ChunkTimeoutError::Timeout`] is returned.
+    /// If `n` is larger than the capacity of the queue,
+    /// [`ChunkTimeoutError::TooFewSlots`] is returned.
+    /// Both contain the number of available slots.
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// [`ChunkTimeoutError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_timeout(
+        &mut self,
+        n: usize,
+        timeout: Duration,
+    ) -> Result<WriteChunk<'_, T>, ChunkTimeoutError>
+    where
+        T: Default,
+    {
+        self.write_chunk_uninit_until(n, deadline_from(timeout))
+            .map(WriteChunk::from)
+    }
+
+    /// Returns `n` slots (initially containing their [`Default`] value) for writing,
+    /// blocking until enough slots are available, but at most until the given `deadline`.
+    ///
+    /// See [`Producer::write_chunk_timeout()`].
+    ///
+    /// # Errors
+    ///
+    /// If not enough slots are available when `deadline` is reached,
+    /// [`ChunkTimeoutError::Timeout`] is returned.
+    /// If `n` is larger than the capacity of the queue,
+    /// [`ChunkTimeoutError::TooFewSlots`] is returned.
+    /// Both contain the number of available slots.
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// [`ChunkTimeoutError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_deadline(
+        &mut self,
+        n: usize,
+        deadline: Instant,
+    ) -> Result<WriteChunk<'_, T>, ChunkTimeoutError>
+    where
+        T: Default,
+    {
+        self.write_chunk_uninit_until(n, Some(deadline))
+            .map(WriteChunk::from)
+    }
+
+    /// Returns `n` (uninitialized) slots for writing, blocking until enough slots are available.
+    ///
+    /// See [`Producer::write_chunk_uninit()`].
+    ///
+    /// # Errors
+    ///
+    /// If `n` is larger than the capacity of the queue, an error
+    /// (containing the number of available slots) is returned.
+    /// If the queue has been closed or the [`Consumer`] has been dropped
+    /// (i.e. no more slots will become available), [`ChunkError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_uninit_blocking(
+        &mut self,
+        n: usize,
+    ) -> Result<WriteChunkUninit<'_, T>, ChunkError> {
+        self.assert_blocking();
+        wait(&self.buffer.producer_waker, None, || {
+            self.write_chunk_ready(n)
+        });
+        self.write_chunk_uninit(n)
+    }
+
+    /// Returns `n` (uninitialized) slots for writing,
+    /// blocking until enough slots are available, but at most for the given `timeout`.
+    ///
+    /// See [`Producer::write_chunk_uninit()`].
+    ///
+    /// # Errors
+    ///
+    /// If not enough slots are available after `timeout` has elapsed,
+    /// [`ChunkTimeoutError::Timeout`] is returned.
+    /// If `n` is larger than the capacity of the queue,
+    /// [`ChunkTimeoutError::TooFewSlots`] is returned.
+    /// Both contain the number of available slots.
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// [`ChunkTimeoutError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_uninit_timeout(
+        &mut self,
+        n: usize,
+        timeout: Duration,
+    ) -> Result<WriteChunkUninit<'_, T>, ChunkTimeoutError> {
+        self.write_chunk_uninit_until(n, deadline_from(timeout))
+    }
+
+    /// Returns `n` (uninitialized) slots for writing,
+    /// blocking until enough slots are available, but at most until the given `deadline`.
+    ///
+    /// See [`Producer::write_chunk_uninit_timeout()`].
+    ///
+    /// # Errors
+    ///
+    /// If not enough slots are available when `deadline` is reached,
+    /// [`ChunkTimeoutError::Timeout`] is returned.
+    /// If `n` is larger than the capacity of the queue,
+    /// [`ChunkTimeoutError::TooFewSlots`] is returned.
+    /// Both contain the number of available slots.
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// [`ChunkTimeoutError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_uninit_deadline(
+        &mut self,
+        n: usize,
+        deadline: Instant,
+    ) -> Result<WriteChunkUninit<'_, T>, ChunkTimeoutError> {
+        self.write_chunk_uninit_until(n, Some(deadline))
+    }
+
+    /// Blocks until a slot is available or until `deadline` (if any) is reached.
+    fn push_until(
+        &mut self,
+        value: T,
+        deadline: Option<Instant>,
+    ) -> Result<(), PushTimeoutError<T>> {
+        self.assert_blocking();
+        wait(&self.buffer.producer_waker, deadline, || self.push_ready());
+        self.push(value).map_err(|e| match e {
+            PushError::Full(value) => PushTimeoutError::Timeout(value),
+            PushError::Closed(value) => PushTimeoutError::Closed(value),
+        })
+    }
+
+    /// Blocks until `n` slots are available or until `deadline` (if any) is reached.
+    fn write_chunk_uninit_until(
+        &mut self,
+        n: usize,
+        deadline: Option<Instant>,
+    ) -> Result<WriteChunkUninit<'_, T>, ChunkTimeoutError> {
+        self.assert_blocking();
+        let ready = wait(&self.buffer.producer_waker, deadline, || {
+            self.write_chunk_ready(n)
+        });
+        self.write_chunk_uninit(n)
+            .map_err(|e| chunk_timeout_error(ready, e))
+    }
+}
+
+impl<T> Consumer<T> {
+    /// Pops an element from the queue, blocking while the queue is empty.
+    ///
+    /// The calling thread is put to sleep until the [`Producer`] makes an element available.
+    ///
+    /// # Errors
+    ///
+    /// If the queue is empty and it has been closed or the [`Producer`] has been dropped
+    /// (i.e. no more elements will become available), an error is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::{Blocking, PopError, RingBuffer};
+    ///
+    /// let (mut p, mut c) = RingBuffer::with_blocking(1, Blocking::Consumer);
+    ///
+    /// std::thread::spawn(move || {
+    ///     while p.push(42).is_err() {}
+    /// });
+    ///
+    /// assert_eq!(c.pop_blocking(), Ok(42));
+    /// // The producer thread has finished, the producer has been dropped:
+    /// assert_eq!(c.pop_blocking(), Err(PopError::Closed));
+    /// ```
+    pub fn pop_blocking(&mut self) -> Result<T, PopError> {
+        self.assert_blocking();
+        wait(&self.buffer.consumer_waker, None, || self.pop_ready());
+        self.pop()
+    }
+
+    /// Pops an element from the queue, blocking while the queue is empty,
+    /// but at most for the given `timeout`.
+    ///
+    /// See [`Consumer::pop_blocking()`].
+    ///
+    /// # Errors
+    ///
+    /// If the queue is still empty after `timeout` has elapsed,
+    /// [`PopTimeoutError::Timeout`] is returned.
+    /// If the queue is empty and it has been closed or the [`Producer`] has been dropped,
+    /// [`PopTimeoutError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    ///
+    /// # Examples
+    ///
+    /// A watchdog thread can detect that the producer has stalled:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rtrb::{Blocking, PopTimeoutError, RingBuffer};
+    ///
+    /// let (mut p, mut c) = RingBuffer::with_blocking(1, Blocking::Consumer);
+    ///
+    /// assert_eq!(p.push(10), Ok(()));
+    /// assert_eq!(c.pop_timeout(Duration::from_millis(1)), Ok(10));
+    /// assert_eq!(
+    ///     c.pop_timeout(Duration::from_millis(1)),
+    ///     Err(PopTimeoutError::Timeout)
+    /// );
+    /// drop(p);
+    /// assert_eq!(
+    ///     c.pop_timeout(Duration::from_millis(1)),
+    ///     Err(PopTimeoutError::Closed)
+    /// );
+    /// ```
+    pub fn pop_timeout(&mut self, timeout: Duration) -> Result<T, PopTimeoutError> {
+        self.pop_until(deadline_from(timeout))
+    }
+
+    /// Pops an element from the queue, blocking while the queue is empty,
+    /// but at most until the given `deadline`.
+    ///
+    /// See [`Consumer::pop_timeout()`].
+    ///
+    /// # Errors
+    ///
+    /// If the queue is still empty when `deadline` is reached,
+    /// [`PopTimeoutError::Timeout`] is returned.
+    /// If the queue is empty and it has been closed or the [`Producer`] has been dropped,
+    /// [`PopTimeoutError::Closed`] is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn pop_deadline(&mut self, deadline: Instant) -> Result<T, PopTimeoutError> {
+        self.pop_until(Some(deadline))
+    }
+
+    /// Returns `n` slots for reading, blocking until enough slots are available.
+    ///
+    /// See [`Consumer::read_chunk()`].
+    ///
+    /// # Errors
+    ///
+    /// If `n` is larger than the capacity of the queue,
+    /// if the queue has been closed or if the [`Producer`] has been dropped
+    /// (i.e. no more slots will become available), an error
+    /// (containing the number of available slots) is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn read_chunk_blocking(&mut self, n: usize) -> Result<ReadChunk<'_, T>, ChunkError> {
+        self.assert_blocking();
+        wait(&self.buffer.consumer_waker, None, || {
+            self.read_chunk_ready(n)
+        });
+        self.read_chunk(n)
+    }
+
+    /// Returns `n` slots for reading,
+    /// blocking until enough slots are available, but at most for the given `timeout`.
+    ///
+    /// See [`Consumer::read_chunk()`].
+    ///
+    /// # Errors
+    ///
+    /// If not enough slots are available after `timeout` has elapsed,
+    /// [`ChunkTimeoutError::Timeout`] is returned.
+    /// If `n` is larger than the capacity of the queue,
+    /// if the queue has been closed or if the [`Producer`] has been dropped,
+    /// [`ChunkTimeoutError::TooFewSlots`] is returned.
+    /// Both contain the number of available slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn read_chunk_timeout(
+        &mut self,
+        n: usize,
+        timeout: Duration,
+    ) -> Result<ReadChunk<'_, T>, ChunkTimeoutError> {
+        self.read_chunk_until(n, deadline_from(timeout))
+    }
+
+    /// Returns `n` slots for reading,
+    /// blocking until enough slots are available, but at most until the given `deadline`.
+    ///
+    /// See [`Consumer::read_chunk_timeout()`].
+    ///
+    /// # Errors
+    ///
+    /// If not enough slots are available when `deadline` is reached,
+    /// [`ChunkTimeoutError::Timeout`] is returned.
+    /// If `n` is larger than the capacity of the queue,
+    /// if the queue has been closed or if the [`Producer`] has been dropped,
+    /// [`ChunkTimeoutError::TooFewSlots`] is returned.
+    /// Both contain the number of available slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn read_chunk_deadline(
+        &mut self,
+        n: usize,
+        deadline: Instant,
+    ) -> Result<ReadChunk<'_, T>, ChunkTimeoutError> {
+        self.read_chunk_until(n, Some(deadline))
+    }
+
+    /// Blocks until an element is available or until `deadline` (if any) is reached.
+    fn pop_until(&mut self, deadline: Option<Instant>) -> Result<T, PopTimeoutError> {
+        self.assert_blocking();
+        wait(&self.buffer.consumer_waker, deadline, || self.pop_ready());
+        self.pop().map_err(|e| match e {
+            PopError::Empty => PopTimeoutError::Timeout,
+            PopError::Closed => PopTimeoutError::Closed,
+        })
+    }
+
+    /// Blocks until `n` slots are available or until `deadline` (if any) is reached.
+    fn read_chunk_until(
+        &mut self,
+        n: usize,
+        deadline: Option<Instant>,
+    ) -> Result<ReadChunk<'_, T>, ChunkTimeoutError> {
+        self.assert_blocking();
+        let ready = wait(&self.buffer.consumer_waker, deadline, || {
+            self.read_chunk_ready(n)
+        });
+        self.read_chunk(n)
+            .map_err(|e| chunk_timeout_error(ready, e))
+    }
+}
+
+/// Wakes up a thread that has been put to sleep with [`thread::park()`].
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Returns the point in time `timeout` from now, or `None` if that is too far in the future.
+fn deadline_from(timeout: Duration) -> Option<Instant> {
+    Instant::now().checked_add(timeout)
+}
+
+fn chunk_timeout_error(ready: bool, error: ChunkError) -> ChunkTimeoutError {
+    match error {
+        ChunkError::TooFewSlots(slots) if ready => ChunkTimeoutError::TooFewSlots(slots),
+        ChunkError::TooFewSlots(slots) => ChunkTimeoutError::Timeout(slots),
+        ChunkError::Closed => ChunkTimeoutError::Closed,
+    }
+}
+
+/// Blocks the current thread until `ready()` returns `true`
+/// or until `deadline` (if any) is reached.
+///
+/// Returns `false` if `deadline` has been reached, `true` otherwise.
+fn wait(waker: &AtomicWaker, deadline: Option<Instant>, mut ready: impl FnMut() -> bool) -> bool {
+    if ready() {
+        return true;
+    }
+    let thread_waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    loop {
+        waker.register(&thread_waker);
+        // The condition has to be checked again after registering,
+        // otherwise a wakeup might be missed.
+        if ready() {
+            return true;
+        }
+        match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return false;
+                }
+                thread::park_timeout(deadline - now);
+            }
+            None => thread::park(),
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! Configuring a [`RingBuffer`] before creating it.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{size_of, ManuallyDrop};
+use core::ptr;
+
+#[cfg(all(feature = "mlock", target_os = "linux"))]
+use crate::mlock::LockedSlots;
+use crate::{max_capacity, Blocking, Consumer, CreateError, Producer, RingBuffer, Storage};
+
+/// A builder for a [`RingBuffer`] with additional options.
+///
+/// Can be created with [`RingBuffer::builder()`].
+///
+/// # Examples
+///
+/// ```
+/// use rtrb::RingBuffer;
+///
+/// let (mut producer, mut consumer) = RingBuffer::builder(1024).prefault(true).build()?;
+/// assert_eq!(producer.push(0.5f32), Ok(()));
+/// assert_eq!(consumer.pop(), Ok(0.5));
+/// # Ok::<(), rtrb::CreateError>(())
+/// ```
+#[derive(Debug)]
+#[must_use]
+pub struct Builder<T> {
+    capacity: usize,
+    storage: Option<Box<dyn Storage<T>>>,
+    producer_blocking: bool,
+    consumer_blocking: bool,
+    overwriting: bool,
+    #[cfg(all(feature = "mlock", target_os = "linux"))]
+    lock_memory: bool,
+    prefault: bool,
+    _marker: PhantomData<T>,
+}
+
+/// An option of a [`Builder`], see [`CreateError::IncompatibleOptions`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BuilderOption {
+    /// [`Builder::blocking()`]
+    Blocking,
+    /// [`Builder::overwriting()`]
+    Overwriting,
+    /// [`Builder::storage()`]
+    Storage,
+    /// `Builder::lock_memory()`
+    LockMemory,
+}
+
+impl fmt::Display for BuilderOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuilderOption::Blocking => "blocking".fmt(f),
+            BuilderOption::Overwriting => "overwriting".fmt(f),
+            BuilderOption::Storage => "storage".fmt(f),
+            BuilderOption::LockMemory => "lock_memory".fmt(f),
+        }
+    }
+}
+
+impl<T> RingBuffer<T> {
+    /// Returns a [`Builder`] for a `RingBuffer` with the given `capacity`.
+    ///
+    /// Calling [`Builder::build()`] without any further options
+    /// is equivalent to [`RingBuffer::try_new()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use rtrb::{Blocking, RingBuffer};
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::builder(2)
+    ///     .overwriting(true)
+    ///     .blocking(Blocking::Consumer)
+    ///     .build()?;
+    /// assert_eq!(producer.force_push(1), None);
+    /// assert_eq!(producer.force_push(2), None);
+    /// assert_eq!(producer.force_push(3), Some(1));
+    /// assert_eq!(consumer.pop_blocking(), Ok(2));
+    /// # }
+    /// # Ok::<(), rtrb::CreateError>(())
+    /// ```
+    pub fn builder(capacity: usize) -> Builder<T> {
+        Builder {
+            capacity,
+            storage: None,
+            producer_blocking: false,
+            consumer_blocking: false,
+            overwriting: false,
+            #[cfg(all(feature = "mlock", target_os = "linux"))]
+            lock_memory: false,
+            prefault: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Builder<T> {
+    /// Allows the given side(s) to block, see [`RingBuffer::with_blocking()`].
+    pub fn blocking(mut self, blocking: Blocking) -> Self {
+        self.producer_blocking = blocking != Blocking::Consumer;
+        self.consumer_blocking = blocking != Blocking::Producer;
+        self
+    }
+
+    /// Allows the [`Producer`] to overwrite the oldest items,
+    /// see [`RingBuffer::new_overwriting()`].
+    ///
+    /// An overwriting [`Producer`] never has to wait for free slots,
+    /// therefore this cannot be combined with blocking on the producer side.
+    pub fn overwriting(mut self, overwriting: bool) -> Self {
+        self.overwriting = overwriting;
+        self
+    }
+
+    /// Uses the given `storage` for the slots, see [`RingBuffer::with_storage()`].
+    ///
+    /// [`Storage::capacity()`] must be the same as the capacity given to
+    /// [`RingBuffer::builder()`].
+    pub fn storage<S>(mut self, storage: S) -> Self
+    where
+        S: Storage<T> + 'static,
+    {
+        self.storage = Some(Box::new(storage));
+        self
+    }
+
+    /// Locks the slots into physical memory with `mlock()`, so they are never swapped out.
+    ///
+    /// Locking the memory also makes sure that all pages are mapped,
+    /// so accessing the slots for the first time doesn't cause any page faults.
+    /// The memory is unlocked when the [`RingBuffer`] is dropped.
+    ///
+    /// The amount of lockable memory is limited (see `ulimit -l`),
+    /// if locking fails, [`Builder::build()`] returns [`CreateError::MemoryLock`].
+    /// This cannot be combined with [`Builder::storage()`].
+    ///
+    /// This is only available on Linux, with the `mlock` feature.
+    #[cfg(all(feature = "mlock", target_os = "linux"))]
+    pub fn lock_memory(mut self, lock_memory: bool) -> Self {
+        self.lock_memory = lock_memory;
+        self
+    }
+
+    /// Touches every page of the slots before returning them.
+    ///
+    /// Freshly allocated memory is typically only mapped by the operating system
+    /// on first access, which would cause page faults on the realtime thread.
+    pub fn prefault(mut self, prefault: bool) -> Self {
+        self.prefault = prefault;
+        self
+    }
+
+    /// Creates the `RingBuffer` and returns [`Producer`] and [`Consumer`].
+    ///
+    /// This allocates memory and possibly makes system calls,
+    /// so it should not be called on a realtime thread.
+    ///
+    /// # Errors
+    ///
+    /// The combination of options is checked before allocating anything,
+    /// [`CreateError::IncompatibleOptions`] and [`CreateError::StorageCapacity`]
+    /// are returned for invalid configurations.
+    /// If `2 * capacity` (or `4 * capacity` for an overwriting queue)
+    /// doesn't fit into a `usize`, [`CreateError::CapacityOverflow`] is returned.
+    /// If the memory for the slots cannot be allocated,
+    /// [`CreateError::AllocError`] is returned.
+    /// If the memory cannot be locked, [`CreateError::MemoryLock`] is returned.
+    pub fn build(self) -> Result<(Producer<T>, Consumer<T>), CreateError> {
+        self.validate()?;
+        let capacity = self.capacity;
+        if capacity > max_capacity(self.overwriting) {
+            return Err(CreateError::CapacityOverflow);
+        }
+        let storage = match self.storage {
+            Some(storage) => Some(storage),
+            #[cfg(all(feature = "mlock", target_os = "linux"))]
+            None if self.lock_memory => {
+                let slots: Box<dyn Storage<T>> = Box::new(LockedSlots::new::<T>(capacity)?);
+                Some(slots)
+            }
+            None => None,
+        };
+        let (data_ptr, storage) = match storage {
+            Some(mut storage) => (storage.as_mut_ptr(), Some(storage)),
+            None => (allocate::<T>(capacity)?, None),
+        };
+        if self.prefault {
+            prefault(data_ptr.cast(), capacity * size_of::<T>());
+        }
+        Ok(RingBuffer::from_raw_parts(
+            data_ptr,
+            capacity,
+            storage,
+            self.producer_blocking,
+            self.consumer_blocking,
+            self.overwriting,
+            false,
+        )
+        .split())
+    }
+
+    /// Checks for invalid combinations of options.
+    fn validate(&self) -> Result<(), CreateError> {
+        if self.overwriting && self.producer_blocking {
+            return Err(CreateError::IncompatibleOptions(
+                BuilderOption::Overwriting,
+                BuilderOption::Blocking,
+            ));
+        }
+        if let Some(storage) = &self.storage {
+            #[cfg(all(feature = "mlock", target_os = "linux"))]
+            if self.lock_memory {
+                return Err(CreateError::IncompatibleOptions(
+                    BuilderOption::Storage,
+                    BuilderOption::LockMemory,
+                ));
+            }
+            if storage.capacity() != self.capacity {
+                return Err(CreateError::StorageCapacity(storage.capacity()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Allocates the slots with a `Vec<T>`.
+fn allocate<T>(capacity: usize) -> Result<*mut T, CreateError> {
+    let mut slots = Vec::new();
+    slots
+        .try_reserve_exact(capacity)
+        .map_err(|_| CreateError::AllocError)?;
+    Ok(ManuallyDrop::new(slots).as_mut_ptr())
+}
+
+/// Writes to each page of the (uninitialized) memory, so that it gets mapped.
+///
+/// Since `data` doesn't have to be page-aligned, one byte is written
+/// at `data` and at the start of each following page up to `data + len`.
+fn prefault(data: *mut u8, len: usize) {
+    let page_size = page_size();
+    let start = data as usize;
+    let mut offset = 0;
+    while offset < len {
+        // Volatile writes cannot be optimized away.
+        unsafe { ptr::write_volatile(data.add(offset), 0) };
+        offset = ((start + offset) / page_size + 1) * page_size - start;
+    }
+}
+
+/// Returns the page size of the operating system.
+#[cfg(all(unix, any(feature = "shm", feature = "mirror", feature = "mlock")))]
+fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+/// Returns the smallest common page size, because `libc` is not available.
+///
+/// If the actual pages are larger, some of them are touched multiple times,
+/// but since [`prefault()`] writes at page boundaries, none of them are missed.
+#[cfg(not(all(unix, any(feature = "shm", feature = "mirror", feature = "mlock"))))]
+fn page_size() -> usize {
+    4096
+}
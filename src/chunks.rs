@@ -119,7 +119,7 @@
 //! Copy as many items as possible from a given slice, returning the number of copied items:
 //!
 //! ```
-//! use rtrb::{Producer, CopyToUninit, chunks::ChunkError::TooFewSlots};
+//! use rtrb::{Producer, CopyToUninit, chunks::ChunkError::{Closed, TooFewSlots}};
 //!
 //! fn push_partial_slice<T>(queue: &mut Producer<T>, slice: &[T]) -> usize
 //! where
@@ -127,8 +127,12 @@
 //! {
 //!     let mut chunk = match queue.write_chunk_uninit(slice.len()) {
 //!         Ok(chunk) => chunk,
-//!         // Remaining slots are returned, this will always succeed:
-//!         Err(TooFewSlots(n)) => queue.write_chunk_uninit(n).unwrap(),
+//!         // Remaining slots are returned, this only fails if the queue is closed meanwhile:
+//!         Err(TooFewSlots(n)) => match queue.write_chunk_uninit(n) {
+//!             Ok(chunk) => chunk,
+//!             Err(_) => return 0,
+//!         },
+//!         Err(Closed) => return 0,
 //!     };
 //!     let end = chunk.len();
 //!     let (first, second) = chunk.as_mut_slices();
@@ -147,7 +151,7 @@
 //! (and return the number of written slots):
 //!
 //! ```
-//! use rtrb::{Producer, chunks::ChunkError::TooFewSlots};
+//! use rtrb::{Producer, chunks::ChunkError::{Closed, TooFewSlots}};
 //!
 //! fn push_from_iter<T, I>(queue: &mut Producer<T>, iter: I) -> usize
 //! where
@@ -161,24 +165,28 @@
 //!     };
 //!     let chunk = match queue.write_chunk_uninit(n) {
 //!         Ok(chunk) => chunk,
-//!         // Remaining slots are returned, this will always succeed:
-//!         Err(TooFewSlots(n)) => queue.write_chunk_uninit(n).unwrap(),
+//!         // Remaining slots are returned, this only fails if the queue is closed meanwhile:
+//!         Err(TooFewSlots(n)) => match queue.write_chunk_uninit(n) {
+//!             Ok(chunk) => chunk,
+//!             Err(_) => return 0,
+//!         },
+//!         Err(Closed) => return 0,
 //!     };
 //!     chunk.fill_from_iter(iter)
 //! }
 //! ```
 
+#[cfg(feature = "alloc")]
+use alloc::sync::Arc;
 use core::fmt;
 use core::mem::MaybeUninit;
 use core::sync::atomic::Ordering;
 
-use crate::{Consumer, CopyToUninit, Producer};
-
-// This is used in the documentation.
-#[allow(unused_imports)]
+#[cfg(feature = "alloc")]
 use crate::RingBuffer;
+use crate::{Consumer, CopyToUninit, Handle, Producer, READING};
 
-impl<T> Producer<T> {
+impl<T, B: Handle<T>> Producer<T, B> {
     /// Returns `n` slots (initially containing their [`Default`] value) for writing.
     ///
     /// [`WriteChunk::as_mut_slices()`] provides mutable access to the slots.
@@ -195,6 +203,8 @@ impl<T> Producer<T> {
     ///
     /// # Errors
     ///
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// [`ChunkError::Closed`] is returned.
     /// If not enough slots are available, an error
     /// (containing the number of available slots) is returned.
     /// Use [`Producer::slots()`] to obtain the number of available slots beforehand.
@@ -202,7 +212,7 @@ impl<T> Producer<T> {
     /// # Examples
     ///
     /// See the documentation of the [`chunks`](crate::chunks#examples) module.
-    pub fn write_chunk(&mut self, n: usize) -> Result<WriteChunk<'_, T>, ChunkError>
+    pub fn write_chunk(&mut self, n: usize) -> Result<WriteChunk<'_, T, B>, ChunkError>
     where
         T: Default,
     {
@@ -223,6 +233,8 @@ impl<T> Producer<T> {
     ///
     /// # Errors
     ///
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// [`ChunkError::Closed`] is returned.
     /// If not enough slots are available, an error
     /// (containing the number of available slots) is returned.
     /// Use [`Producer::slots()`] to obtain the number of available slots beforehand.
@@ -236,23 +248,34 @@ impl<T> Producer<T> {
     ///
     /// For a safe alternative that provides mutable slices of [`Default`]-initialized slots,
     /// see [`Producer::write_chunk()`].
-    pub fn write_chunk_uninit(&mut self, n: usize) -> Result<WriteChunkUninit<'_, T>, ChunkError> {
+    pub fn write_chunk_uninit(
+        &mut self,
+        n: usize,
+    ) -> Result<WriteChunkUninit<'_, T, B>, ChunkError> {
+        if self.buffer.push_closed() {
+            return Err(ChunkError::Closed);
+        }
         let tail = self.cached_tail.get();
 
         // Check if the queue has *possibly* not enough slots.
-        if self.buffer.capacity - self.buffer.distance(self.cached_head.get(), tail) < n {
+        if self.buffer.capacity
+            - self
+                .buffer
+                .distance(self.cached_head.get(), tail, B::POWER_OF_TWO)
+            < n
+        {
             // Refresh the head ...
-            let head = self.buffer.head.load(Ordering::Acquire);
+            let head = self.buffer.load_head();
             self.cached_head.set(head);
 
             // ... and check if there *really* are not enough slots.
-            let slots = self.buffer.capacity - self.buffer.distance(head, tail);
+            let slots = self.buffer.capacity - self.buffer.distance(head, tail, B::POWER_OF_TWO);
             if slots < n {
                 return Err(ChunkError::TooFewSlots(slots));
             }
         }
-        let tail = self.buffer.collapse_position(tail);
-        let first_len = n.min(self.buffer.capacity - tail);
+        let tail = self.buffer.collapse_position(tail, B::POWER_OF_TWO);
+        let first_len = self.buffer.first_len(tail, n);
         Ok(WriteChunkUninit {
             first_ptr: unsafe { self.buffer.data_ptr.add(tail) },
             first_len,
@@ -261,9 +284,119 @@ impl<T> Producer<T> {
             producer: self,
         })
     }
+
+    /// Returns `n` slots (initially containing their [`Default`] value) for writing,
+    /// discarding the oldest items if necessary.
+    ///
+    /// This is the overwriting counterpart of [`Producer::write_chunk()`],
+    /// see [`Producer::write_chunk_uninit_overwrite()`] for details.
+    ///
+    /// # Errors
+    ///
+    /// See [`Producer::write_chunk_uninit_overwrite()`].
+    ///
+    /// # Panics
+    ///
+    /// This panics if the queue has not been created with [`RingBuffer::new_overwriting()`].
+    pub fn write_chunk_overwrite(&mut self, n: usize) -> Result<WriteChunk<'_, T, B>, ChunkError>
+    where
+        T: Default,
+    {
+        self.write_chunk_uninit_overwrite(n).map(WriteChunk::from)
+    }
+
+    /// Returns `n` (uninitialized) slots for writing, discarding the oldest items if necessary.
+    ///
+    /// This is only allowed if the queue has been created with
+    /// [`RingBuffer::new_overwriting()`].
+    ///
+    /// If fewer than `n` slots are available, the oldest items are dropped
+    /// (on the producer's thread!) to make room and
+    /// [`Consumer::dropped_items()`] is incremented accordingly.
+    /// Apart from that, this behaves like [`Producer::write_chunk_uninit()`].
+    ///
+    /// # Errors
+    ///
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// [`ChunkError::Closed`] is returned (and no items are discarded).
+    /// If `n` is larger than the capacity or if the [`Consumer`] is currently
+    /// reading from the queue (which means that the oldest items cannot be discarded)
+    /// and not enough slots are available, an error
+    /// (containing the number of available slots) is returned.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the queue has not been created with [`RingBuffer::new_overwriting()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::{CopyToUninit, RingBuffer};
+    ///
+    /// let (mut p, mut c) = RingBuffer::new_overwriting(4);
+    ///
+    /// let mut chunk = p.write_chunk_uninit_overwrite(3).unwrap();
+    /// let (first, _) = chunk.as_mut_slices();
+    /// [1, 2, 3].copy_to_uninit(first);
+    /// unsafe { chunk.commit_all() };
+    ///
+    /// let mut chunk = p.write_chunk_uninit_overwrite(3).unwrap();
+    /// let (first, second) = chunk.as_mut_slices();
+    /// [4].copy_to_uninit(first);
+    /// [5, 6].copy_to_uninit(second);
+    /// unsafe { chunk.commit_all() };
+    ///
+    /// assert_eq!(c.dropped_items(), 2);
+    /// let chunk = c.read_chunk(4).unwrap();
+    /// assert!(chunk.into_iter().eq([3, 4, 5, 6]));
+    /// ```
+    pub fn write_chunk_uninit_overwrite(
+        &mut self,
+        n: usize,
+    ) -> Result<WriteChunkUninit<'_, T, B>, ChunkError> {
+        self.assert_overwriting();
+        if self.buffer.push_closed() {
+            return Err(ChunkError::Closed);
+        }
+        if n > self.buffer.capacity {
+            return Err(ChunkError::TooFewSlots(self.slots()));
+        }
+        let tail = self.cached_tail.get();
+        let mut head = self.buffer.head.load(Ordering::Acquire);
+        // The head can only be moved forward if the consumer is not reading.
+        while head & READING == 0 {
+            let slots = self.buffer.capacity - self.buffer.distance(head, tail, B::POWER_OF_TWO);
+            if slots >= n {
+                break;
+            }
+            let discard = n - slots;
+            let new_head = self.buffer.increment(head, discard, B::POWER_OF_TWO);
+            match self.buffer.head.compare_exchange(
+                head,
+                new_head,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.cached_head.set(new_head);
+                    let mut pos = head;
+                    for _ in 0..discard {
+                        unsafe { self.buffer.slot_ptr(pos, B::POWER_OF_TWO).drop_in_place() };
+                        pos = self.buffer.increment1(pos, B::POWER_OF_TWO);
+                    }
+                    self.buffer.dropped.fetch_add(discard, Ordering::Relaxed);
+                    break;
+                }
+                // The consumer has popped items or started reading in the meantime.
+                Err(current) => head = current,
+            }
+        }
+        // This can only fail if the consumer is currently reading.
+        self.write_chunk_uninit(n)
+    }
 }
 
-impl<T> Consumer<T> {
+impl<T, B: Handle<T>> Consumer<T, B> {
     /// Returns `n` slots for reading.
     ///
     /// [`ReadChunk::as_slices()`] provides immutable access to the slots.
@@ -285,24 +418,30 @@ impl<T> Consumer<T> {
     /// # Examples
     ///
     /// See the documentation of the [`chunks`](crate::chunks#examples) module.
-    pub fn read_chunk(&mut self, n: usize) -> Result<ReadChunk<'_, T>, ChunkError> {
+    pub fn read_chunk(&mut self, n: usize) -> Result<ReadChunk<'_, T, B>, ChunkError> {
+        self.claim_head();
         let head = self.cached_head.get();
 
         // Check if the queue has *possibly* not enough slots.
-        if self.buffer.distance(head, self.cached_tail.get()) < n {
+        if self
+            .buffer
+            .distance(head, self.cached_tail.get(), B::POWER_OF_TWO)
+            < n
+        {
             // Refresh the tail ...
             let tail = self.buffer.tail.load(Ordering::Acquire);
             self.cached_tail.set(tail);
 
             // ... and check if there *really* are not enough slots.
-            let slots = self.buffer.distance(head, tail);
+            let slots = self.buffer.distance(head, tail, B::POWER_OF_TWO);
             if slots < n {
+                self.release_head();
                 return Err(ChunkError::TooFewSlots(slots));
             }
         }
 
-        let head = self.buffer.collapse_position(head);
-        let first_len = n.min(self.buffer.capacity - head);
+        let head = self.buffer.collapse_position(head, B::POWER_OF_TWO);
+        let first_len = self.buffer.first_len(head, n);
         Ok(ReadChunk {
             first_ptr: unsafe { self.buffer.data_ptr.add(head) },
             first_len,
@@ -321,9 +460,14 @@ impl<T> Consumer<T> {
 /// which also allows moving items from an iterator into the ring buffer
 /// by means of [`WriteChunkUninit::fill_from_iter()`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct WriteChunk<'a, T>(Option<WriteChunkUninit<'a, T>>);
+pub struct WriteChunk<
+    'a,
+    T,
+    #[cfg(feature = "alloc")] B: Handle<T> = Arc<RingBuffer<T>>,
+    #[cfg(not(feature = "alloc"))] B: Handle<T>,
+>(Option<WriteChunkUninit<'a, T, B>>);
 
-impl<T> Drop for WriteChunk<'_, T> {
+impl<T, B: Handle<T>> Drop for WriteChunk<'_, T, B> {
     fn drop(&mut self) {
         // NB: If `commit()` or `commit_all()` has been called, `self.0` is `None`.
         if let Some(mut chunk) = self.0.take() {
@@ -336,12 +480,12 @@ impl<T> Drop for WriteChunk<'_, T> {
     }
 }
 
-impl<'a, T> From<WriteChunkUninit<'a, T>> for WriteChunk<'a, T>
+impl<'a, T, B: Handle<T>> From<WriteChunkUninit<'a, T, B>> for WriteChunk<'a, T, B>
 where
     T: Default,
 {
     /// Fills all slots with the [`Default`] value.
-    fn from(chunk: WriteChunkUninit<'a, T>) -> Self {
+    fn from(chunk: WriteChunkUninit<'a, T, B>) -> Self {
         for i in 0..chunk.first_len {
             unsafe {
                 chunk.first_ptr.add(i).write(Default::default());
@@ -356,7 +500,7 @@ where
     }
 }
 
-impl<T> WriteChunk<'_, T>
+impl<T, B: Handle<T>> WriteChunk<'_, T, B>
 where
     T: Default,
 {
@@ -386,6 +530,22 @@ where
         }
     }
 
+    /// Returns a single slice for writing to the requested slots.
+    ///
+    /// This is only possible if the queue has been created with
+    /// [`RingBuffer::new_mirrored()`], otherwise `None` is returned
+    /// and [`as_mut_slices()`](WriteChunk::as_mut_slices) has to be used.
+    #[cfg(all(feature = "mirror", target_os = "linux"))]
+    pub fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        // self.0 is always Some(chunk).
+        let chunk = self.0.as_ref().unwrap();
+        if !chunk.producer.buffer.mirrored {
+            return None;
+        }
+        // Safety: All slots have been initialized in From::from().
+        Some(unsafe { core::slice::from_raw_parts_mut(chunk.first_ptr, chunk.first_len) })
+    }
+
     /// Makes the first `n` slots of the chunk available for reading.
     ///
     /// The rest of the chunk is dropped.
@@ -436,15 +596,20 @@ where
 ///
 /// This is returned from [`Producer::write_chunk_uninit()`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct WriteChunkUninit<'a, T> {
+pub struct WriteChunkUninit<
+    'a,
+    T,
+    #[cfg(feature = "alloc")] B: Handle<T> = Arc<RingBuffer<T>>,
+    #[cfg(not(feature = "alloc"))] B: Handle<T>,
+> {
     first_ptr: *mut T,
     first_len: usize,
     second_ptr: *mut T,
     second_len: usize,
-    producer: &'a Producer<T>,
+    producer: &'a Producer<T, B>,
 }
 
-impl<T> WriteChunkUninit<'_, T> {
+impl<T, B: Handle<T>> WriteChunkUninit<'_, T, B> {
     /// Returns two slices for writing to the requested slots.
     ///
     /// The first slice can only be empty if `0` slots have been requested.
@@ -468,6 +633,37 @@ impl<T> WriteChunkUninit<'_, T> {
         }
     }
 
+    /// Returns a single slice for writing to the requested slots.
+    ///
+    /// This is only possible if the queue has been created with
+    /// [`RingBuffer::new_mirrored()`], otherwise `None` is returned
+    /// and [`as_mut_slices()`](WriteChunkUninit::as_mut_slices) has to be used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::{CopyToUninit, RingBuffer};
+    ///
+    /// let (mut p, mut c) = RingBuffer::new_mirrored(4096).unwrap();
+    /// let data = [1u8; 3000];
+    /// for _ in 0..3 {
+    ///     let mut chunk = p.write_chunk_uninit(data.len()).unwrap();
+    ///     // No need to split the data, even if the chunk wraps around:
+    ///     data.copy_to_uninit(chunk.as_mut_slice().unwrap());
+    ///     unsafe { chunk.commit_all() };
+    ///     let chunk = c.read_chunk(data.len()).unwrap();
+    ///     assert_eq!(chunk.as_slice().unwrap(), data);
+    ///     chunk.commit_all();
+    /// }
+    /// ```
+    #[cfg(all(feature = "mirror", target_os = "linux"))]
+    pub fn as_mut_slice(&mut self) -> Option<&mut [MaybeUninit<T>]> {
+        if !self.producer.buffer.mirrored {
+            return None;
+        }
+        Some(unsafe { core::slice::from_raw_parts_mut(self.first_ptr as *mut _, self.first_len) })
+    }
+
     /// Makes the first `n` slots of the chunk available for reading.
     ///
     /// # Panics
@@ -494,9 +690,10 @@ impl<T> WriteChunkUninit<'_, T> {
 
     unsafe fn commit_unchecked(self, n: usize) -> usize {
         let p = self.producer;
-        let tail = p.buffer.increment(p.cached_tail.get(), n);
+        let tail = p.buffer.increment(p.cached_tail.get(), n, B::POWER_OF_TWO);
         p.buffer.tail.store(tail, Ordering::Release);
         p.cached_tail.set(tail);
+        p.buffer.wake_consumer();
         n
     }
 
@@ -604,17 +801,22 @@ impl<T> WriteChunkUninit<'_, T> {
 ///
 /// This is returned from [`Consumer::read_chunk()`].
 #[derive(Debug, PartialEq, Eq)]
-pub struct ReadChunk<'a, T> {
+pub struct ReadChunk<
+    'a,
+    T,
+    #[cfg(feature = "alloc")] B: Handle<T> = Arc<RingBuffer<T>>,
+    #[cfg(not(feature = "alloc"))] B: Handle<T>,
+> {
     // Must be "mut" for drop_in_place()
     first_ptr: *mut T,
     first_len: usize,
     // Must be "mut" for drop_in_place()
     second_ptr: *mut T,
     second_len: usize,
-    consumer: &'a mut Consumer<T>,
+    consumer: &'a mut Consumer<T, B>,
 }
 
-impl<T> ReadChunk<'_, T> {
+impl<T, B: Handle<T>> ReadChunk<'_, T, B> {
     /// Returns two slices for reading from the requested slots.
     ///
     /// The first slice can only be empty if `0` slots have been requested.
@@ -634,6 +836,20 @@ impl<T> ReadChunk<'_, T> {
         )
     }
 
+    /// Returns a single slice for reading from the requested slots.
+    ///
+    /// This is only possible if the queue has been created with
+    /// [`RingBuffer::new_mirrored()`], otherwise `None` is returned
+    /// and [`as_slices()`](ReadChunk::as_slices) has to be used.
+    #[cfg(all(feature = "mirror", target_os = "linux"))]
+    #[must_use]
+    pub fn as_slice(&self) -> Option<&[T]> {
+        if !self.consumer.buffer.mirrored {
+            return None;
+        }
+        Some(unsafe { core::slice::from_raw_parts(self.first_ptr, self.first_len) })
+    }
+
     /// Drops the first `n` slots of the chunk, making the space available for writing again.
     ///
     /// # Panics
@@ -707,9 +923,10 @@ impl<T> ReadChunk<'_, T> {
             self.second_ptr.add(i).drop_in_place();
         }
         let c = self.consumer;
-        let head = c.buffer.increment(c.cached_head.get(), n);
+        let head = c.buffer.increment(c.cached_head.get(), n, B::POWER_OF_TWO);
         c.buffer.head.store(head, Ordering::Release);
         c.cached_head.set(head);
+        c.buffer.wake_producer();
         n
     }
 
@@ -726,9 +943,9 @@ impl<T> ReadChunk<'_, T> {
     }
 }
 
-impl<'a, T> IntoIterator for ReadChunk<'a, T> {
+impl<'a, T, B: Handle<T>> IntoIterator for ReadChunk<'a, T, B> {
     type Item = T;
-    type IntoIter = ReadChunkIntoIter<'a, T>;
+    type IntoIter = ReadChunkIntoIter<'a, T, B>;
 
     /// Turns a [`ReadChunk`] into an iterator.
     ///
@@ -750,24 +967,32 @@ impl<'a, T> IntoIterator for ReadChunk<'a, T> {
 /// When this `struct` is dropped, the iterated slots are made available for writing again.
 /// Non-iterated items remain in the ring buffer.
 #[derive(Debug)]
-pub struct ReadChunkIntoIter<'a, T> {
-    chunk: ReadChunk<'a, T>,
+pub struct ReadChunkIntoIter<
+    'a,
+    T,
+    #[cfg(feature = "alloc")] B: Handle<T> = Arc<RingBuffer<T>>,
+    #[cfg(not(feature = "alloc"))] B: Handle<T>,
+> {
+    chunk: ReadChunk<'a, T, B>,
     iterated: usize,
 }
 
-impl<'a, T> Drop for ReadChunkIntoIter<'a, T> {
+impl<'a, T, B: Handle<T>> Drop for ReadChunkIntoIter<'a, T, B> {
     /// Makes all iterated slots available for writing again.
     ///
     /// Non-iterated items remain in the ring buffer and are *not* dropped.
     fn drop(&mut self) {
         let c = &self.chunk.consumer;
-        let head = c.buffer.increment(c.cached_head.get(), self.iterated);
+        let head = c
+            .buffer
+            .increment(c.cached_head.get(), self.iterated, B::POWER_OF_TWO);
         c.buffer.head.store(head, Ordering::Release);
         c.cached_head.set(head);
+        c.buffer.wake_producer();
     }
 }
 
-impl<'a, T> Iterator for ReadChunkIntoIter<'a, T> {
+impl<'a, T, B: Handle<T>> Iterator for ReadChunkIntoIter<'a, T, B> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -792,19 +1017,30 @@ impl<'a, T> Iterator for ReadChunkIntoIter<'a, T> {
     }
 }
 
-impl<'a, T> ExactSizeIterator for ReadChunkIntoIter<'a, T> {}
+impl<'a, T, B: Handle<T>> ExactSizeIterator for ReadChunkIntoIter<'a, T, B> {}
 
-impl<'a, T> core::iter::FusedIterator for ReadChunkIntoIter<'a, T> {}
+impl<'a, T, B: Handle<T>> core::iter::FusedIterator for ReadChunkIntoIter<'a, T, B> {}
 
 #[cfg(feature = "std")]
-impl std::io::Write for Producer<u8> {
+impl<B: Handle<u8>> std::io::Write for Producer<u8, B> {
+    /// Writes as many bytes as possible without blocking.
+    ///
+    /// If no slots are available, an error of kind
+    /// [`WouldBlock`](std::io::ErrorKind::WouldBlock) is returned.
+    /// If the queue has been closed or the [`Consumer`] has been dropped, an error of kind
+    /// [`BrokenPipe`](std::io::ErrorKind::BrokenPipe) is returned.
     #[inline]
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        use ChunkError::TooFewSlots;
-        let mut chunk = match self.write_chunk_uninit(buf.len()) {
-            Ok(chunk) => chunk,
-            Err(TooFewSlots(0)) => return Err(std::io::ErrorKind::WouldBlock.into()),
-            Err(TooFewSlots(n)) => self.write_chunk_uninit(n).unwrap(),
+        use ChunkError::{Closed, TooFewSlots};
+        let mut n = buf.len();
+        let mut chunk = loop {
+            match self.write_chunk_uninit(n) {
+                Ok(chunk) => break chunk,
+                Err(Closed) => return Err(std::io::ErrorKind::BrokenPipe.into()),
+                Err(TooFewSlots(0)) => return Err(std::io::ErrorKind::WouldBlock.into()),
+                // The queue might be closed in the meantime, so this can still fail.
+                Err(TooFewSlots(available)) => n = available,
+            }
         };
         let end = chunk.len();
         let (first, second) = chunk.as_mut_slices();
@@ -827,14 +1063,31 @@ impl std::io::Write for Producer<u8> {
 }
 
 #[cfg(feature = "std")]
-impl std::io::Read for Consumer<u8> {
+impl<B: Handle<u8>> std::io::Read for Consumer<u8, B> {
+    /// Reads as many bytes as possible without blocking.
+    ///
+    /// If no bytes are available, an error of kind
+    /// [`WouldBlock`](std::io::ErrorKind::WouldBlock) is returned.
+    /// If no bytes are available and the queue has been closed
+    /// or the [`Producer`] has been dropped,
+    /// `Ok(0)` is returned, signaling the end of the stream.
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        use ChunkError::TooFewSlots;
-        let chunk = match self.read_chunk(buf.len()) {
-            Ok(chunk) => chunk,
-            Err(TooFewSlots(0)) => return Err(std::io::ErrorKind::WouldBlock.into()),
-            Err(TooFewSlots(n)) => self.read_chunk(n).unwrap(),
+        use ChunkError::{Closed, TooFewSlots};
+        // NB: This has to be checked before looking at the available slots,
+        //     otherwise the last bytes might be missed.
+        let closed = self.buffer.pop_closed();
+        let mut n = buf.len();
+        let chunk = loop {
+            match self.read_chunk(n) {
+                Ok(chunk) => break chunk,
+                Err(TooFewSlots(0)) if closed => return Ok(0),
+                Err(TooFewSlots(0)) => return Err(std::io::ErrorKind::WouldBlock.into()),
+                // In an overwriting queue, the number of slots might shrink in the meantime,
+                // but it is always smaller than the requested number, so this terminates.
+                Err(TooFewSlots(available)) => n = available,
+                Err(Closed) => unreachable!("only returned when writing"),
+            }
         };
         let (first, second) = chunk.as_slices();
         let mid = first.len();
@@ -855,6 +1108,9 @@ pub enum ChunkError {
     ///
     /// Contains the number of slots that were available.
     TooFewSlots(usize),
+    /// The queue has been closed or the [`Consumer`] has been dropped
+    /// (only returned when writing).
+    Closed,
 }
 
 #[cfg(feature = "std")]
@@ -863,9 +1119,52 @@ impl std::error::Error for ChunkError {}
 impl fmt::Display for ChunkError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            #[cfg(feature = "alloc")]
             ChunkError::TooFewSlots(n) => {
                 alloc::format!("only {} slots available in ring buffer", n).fmt(f)
             }
+            // Without an allocator, padding and alignment are not supported.
+            #[cfg(not(feature = "alloc"))]
+            ChunkError::TooFewSlots(n) => write!(f, "only {} slots available in ring buffer", n),
+            ChunkError::Closed => "closed ring buffer".fmt(f),
+        }
+    }
+}
+
+/// Error type for the timed variants of [`Consumer::read_chunk()`],
+/// [`Producer::write_chunk()`] and [`Producer::write_chunk_uninit()`],
+/// e.g. [`Consumer::read_chunk_timeout()`].
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChunkTimeoutError {
+    /// Fewer than the requested number of slots were available
+    /// and no more will become available (or more than the capacity was requested).
+    ///
+    /// Contains the number of slots that were available.
+    TooFewSlots(usize),
+    /// Fewer than the requested number of slots were available when the timeout expired.
+    ///
+    /// Contains the number of slots that were available.
+    Timeout(usize),
+    /// The queue has been closed or the [`Consumer`] has been dropped
+    /// (only returned when writing).
+    Closed,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChunkTimeoutError {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for ChunkTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkTimeoutError::TooFewSlots(n) => {
+                alloc::format!("only {} slots available in ring buffer", n).fmt(f)
+            }
+            ChunkTimeoutError::Timeout(n) => {
+                alloc::format!("timed out with only {} slots available in ring buffer", n).fmt(f)
+            }
+            ChunkTimeoutError::Closed => "closed ring buffer".fmt(f),
         }
     }
 }
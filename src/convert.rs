@@ -0,0 +1,249 @@
+//! Conversions between an owned [`RingBuffer`], its [`Producer`]/[`Consumer`]
+//! and standard collections.
+
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem::{size_of, ManuallyDrop, MaybeUninit};
+
+use crate::{Consumer, Producer, RingBuffer, READING};
+
+impl<T> RingBuffer<T> {
+    /// Takes back ownership of the ring buffer from its [`Producer`] and [`Consumer`].
+    ///
+    /// Items that are still in the queue are *not* dropped.
+    /// The returned `RingBuffer` can be split again with [`RingBuffer::split()`]
+    /// (re-using its memory) or it can be converted into a [`Vec`] or a [`VecDeque`]
+    /// containing the remaining items.
+    ///
+    /// # Errors
+    ///
+    /// If the `producer` and the `consumer` don't belong to the same ring buffer
+    /// (which is checked with [`PartialEq`]), both are returned in a [`ReuniteError`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::VecDeque;
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, consumer) = RingBuffer::new(3);
+    /// producer.push(1).unwrap();
+    /// producer.push(2).unwrap();
+    ///
+    /// let buffer = RingBuffer::reunite(producer, consumer).unwrap();
+    /// assert_eq!(VecDeque::from(buffer), [1, 2]);
+    /// ```
+    pub fn reunite(
+        producer: Producer<T>,
+        consumer: Consumer<T>,
+    ) -> Result<RingBuffer<T>, ReuniteError<T>> {
+        if producer.buffer() != consumer.buffer() {
+            return Err(ReuniteError(producer, consumer));
+        }
+        // Neither side is dropped, so that the state flags are not modified.
+        let producer = ManuallyDrop::new(producer);
+        let consumer = ManuallyDrop::new(consumer);
+        let (p, c) = unsafe {
+            (
+                core::ptr::read(&producer.buffer),
+                core::ptr::read(&consumer.buffer),
+            )
+        };
+        drop(p);
+        // There are no other references to the ring buffer.
+        Ok(Arc::try_unwrap(c).ok().unwrap())
+    }
+
+    /// Returns a [`Producer`] and a [`Consumer`] for an owned ring buffer.
+    ///
+    /// Items that are still in the queue are kept,
+    /// but the queue is neither closed nor abandoned anymore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::new(2);
+    /// producer.push("hello").unwrap();
+    /// producer.close();
+    ///
+    /// let buffer = RingBuffer::reunite(producer, consumer).unwrap();
+    /// let (mut producer, mut consumer) = buffer.split();
+    /// assert_eq!(producer.push("world"), Ok(()));
+    /// assert_eq!(consumer.pop(), Ok("hello"));
+    /// assert_eq!(consumer.pop(), Ok("world"));
+    /// ```
+    #[must_use]
+    pub fn split(mut self) -> (Producer<T>, Consumer<T>) {
+        *self.state.get_mut() = 0;
+        let head = *self.head.get_mut() & !READING;
+        let tail = *self.tail.get_mut();
+        let buffer = Arc::new(self);
+        let p = Producer {
+            buffer: buffer.clone(),
+            cached_head: head.into(),
+            cached_tail: tail.into(),
+        };
+        let c = Consumer {
+            buffer,
+            cached_head: head.into(),
+            cached_tail: tail.into(),
+        };
+        (p, c)
+    }
+}
+
+impl<T> RingBuffer<T> {
+    /// Creates a `RingBuffer` that initially contains the items from `iter`.
+    ///
+    /// The returned [`Producer`] and [`Consumer`] behave as if the items
+    /// had been pushed one by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `iter` yields more than `capacity` items.
+    ///
+    /// # Examples
+    ///
+    /// Priming a queue with silence:
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) =
+    ///     RingBuffer::from_iter_with_capacity(8, std::iter::repeat(0.0f32).take(3));
+    /// assert_eq!(producer.slots(), 5);
+    /// assert_eq!(consumer.slots(), 3);
+    /// assert_eq!(consumer.pop(), Ok(0.0));
+    /// ```
+    pub fn from_iter_with_capacity<I>(capacity: usize, iter: I) -> (Producer<T>, Consumer<T>)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let mut items = Vec::with_capacity(capacity);
+        items.extend(iter.by_ref().take(capacity));
+        assert!(
+            iter.next().is_none(),
+            "too many items for the given capacity"
+        );
+        Self::from_vec(items, capacity).split()
+    }
+
+    /// Creates a `RingBuffer` holding `items`, re-using their allocation.
+    ///
+    /// `capacity` must be the capacity of the `Vec`, except for zero-sized types,
+    /// where it must be at least the length.
+    fn from_vec(items: Vec<T>, capacity: usize) -> RingBuffer<T> {
+        let len = items.len();
+        let mut buffer = Self::from_raw_parts(
+            ManuallyDrop::new(items).as_mut_ptr(),
+            capacity,
+            None,
+            false,
+            false,
+            false,
+            false,
+        );
+        *buffer.tail.get_mut() = len;
+        buffer
+    }
+}
+
+impl<T> From<Vec<T>> for RingBuffer<T> {
+    /// Creates a `RingBuffer` containing the items of the `Vec`, re-using its allocation.
+    ///
+    /// The capacity of the queue is the capacity of the `Vec`
+    /// (except for zero-sized types, where it is the length).
+    /// Use [`RingBuffer::split()`] to obtain a [`Producer`] and a [`Consumer`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let mut items = Vec::with_capacity(4);
+    /// items.extend([1, 2]);
+    /// let (mut producer, mut consumer) = RingBuffer::from(items).split();
+    /// assert_eq!(producer.slots(), 2);
+    /// assert_eq!(consumer.pop(), Ok(1));
+    /// assert_eq!(consumer.pop(), Ok(2));
+    /// ```
+    fn from(items: Vec<T>) -> Self {
+        let capacity = if size_of::<T>() == 0 {
+            items.len()
+        } else {
+            items.capacity()
+        };
+        Self::from_vec(items, capacity)
+    }
+}
+
+impl<T> From<RingBuffer<T>> for Vec<T> {
+    /// Moves the remaining items into a `Vec`.
+    ///
+    /// If the slots have been allocated by [`RingBuffer::new()`] (or similar),
+    /// the allocation is re-used.
+    /// If custom storage has been used, the items are moved into a new `Vec`.
+    fn from(mut buffer: RingBuffer<T>) -> Self {
+        let mut head = *buffer.head.get_mut() & !READING;
+        let tail = *buffer.tail.get_mut();
+        let len = buffer.distance(head, tail, false);
+        let capacity = buffer.capacity;
+        let mut items;
+        if buffer.storage.is_none() {
+            // Move the items to the beginning of the allocation.
+            let slots = unsafe {
+                core::slice::from_raw_parts_mut(buffer.data_ptr as *mut MaybeUninit<T>, capacity)
+            };
+            slots.rotate_left(buffer.collapse_position(head, false));
+            items = unsafe { Vec::from_raw_parts(buffer.data_ptr, len, capacity) };
+            // The allocation is now owned by `items`, an empty `Vec` is dropped instead.
+            buffer.data_ptr = core::ptr::NonNull::dangling().as_ptr();
+            buffer.capacity = 0;
+            head = 0;
+            *buffer.tail.get_mut() = 0;
+        } else {
+            items = Vec::with_capacity(len);
+            while head != tail {
+                items.push(unsafe { buffer.slot_ptr(head, false).read() });
+                head = buffer.increment1(head, false);
+            }
+        }
+        // All items have been moved out.
+        *buffer.head.get_mut() = head;
+        items
+    }
+}
+
+impl<T> From<RingBuffer<T>> for VecDeque<T> {
+    /// Moves the remaining items into a `VecDeque`.
+    ///
+    /// See the conversion into [`Vec`] for when the allocation is re-used.
+    fn from(buffer: RingBuffer<T>) -> Self {
+        Vec::from(buffer).into()
+    }
+}
+
+/// Error type for [`RingBuffer::reunite()`].
+///
+/// It contains the [`Producer`] and the [`Consumer`] that were passed in.
+pub struct ReuniteError<T>(pub Producer<T>, pub Consumer<T>);
+
+impl<T> fmt::Debug for ReuniteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ReuniteError(..)")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for ReuniteError<T> {}
+
+impl<T> fmt::Display for ReuniteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "producer and consumer belong to different ring buffers".fmt(f)
+    }
+}
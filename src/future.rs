@@ -0,0 +1,516 @@
+//! Asynchronous variants of the reading and writing functions.
+//!
+//! The [`Future`]s in this module can be `.await`ed in an asynchronous context.
+//! They are created with [`Producer::push_async()`], [`Producer::write_chunk_async()`],
+//! [`Producer::write_chunk_uninit_async()`], [`Consumer::pop_async()`]
+//! and [`Consumer::read_chunk_async()`].
+//!
+//! Like the blocking functions, asynchronous waiting has to be enabled separately for each side,
+//! see [`RingBuffer::with_blocking()`].
+//! The other side never has to wait for anything and it never clones a [`Waker`].
+//! After each commit, it only needs a memory fence and an atomic load
+//! as long as no task is waiting.
+//! If a task is actually waiting, it calls [`Waker::wake_by_ref()`],
+//! which runs code of the executor that is not necessarily realtime-safe.
+//! The registered [`Waker`] is dropped together with the ring buffer,
+//! i.e. by the side that drops its handle last.
+//!
+//! If the `futures` feature is enabled, [`Consumer`] implements `futures::Stream`
+//! and [`Producer`] implements `futures::Sink`.
+//! If the `tokio` feature is enabled, `Consumer<u8>` implements `tokio::io::AsyncRead`
+//! and `Producer<u8>` implements `tokio::io::AsyncWrite`.
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::{Blocking, RingBuffer};
+//!
+//! async fn sum(mut consumer: rtrb::Consumer<i32>) -> i32 {
+//!     let mut sum = 0;
+//!     // An error is returned after the producer has been dropped:
+//!     while let Ok(value) = consumer.pop_async().await {
+//!         sum += value;
+//!     }
+//!     sum
+//! }
+//!
+//! let (mut producer, consumer) = RingBuffer::with_blocking(2, Blocking::Consumer);
+//!
+//! let future = sum(consumer);
+//! // The future can be spawned on any executor.
+//! # let consumer_thread = std::thread::spawn(move || block_on(future));
+//! for i in 1..=10 {
+//!     while producer.push(i).is_err() {}
+//! }
+//! drop(producer);
+//! # assert_eq!(consumer_thread.join().unwrap(), 55);
+//! #
+//! # fn block_on<F: std::future::Future>(future: F) -> F::Output {
+//! #     use std::sync::Arc;
+//! #     use std::task::{Context, Poll, Wake, Waker};
+//! #     struct ThreadWaker(std::thread::Thread);
+//! #     impl Wake for ThreadWaker {
+//! #         fn wake(self: Arc<Self>) {
+//! #             self.0.unpark();
+//! #         }
+//! #     }
+//! #     let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+//! #     let mut cx = Context::from_waker(&waker);
+//! #     let mut future = Box::pin(future);
+//! #     loop {
+//! #         match future.as_mut().poll(&mut cx) {
+//! #             Poll::Ready(output) => return output,
+//! #             Poll::Pending => std::thread::park(),
+//! #         }
+//! #     }
+//! # }
+//! ```
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+use crate::chunks::{ChunkError, ReadChunk, WriteChunk, WriteChunkUninit};
+use crate::waker::AtomicWaker;
+use crate::{Consumer, PopError, Producer, PushError};
+
+// This is used in the documentation.
+#[allow(unused_imports)]
+use crate::RingBuffer;
+
+impl<T> Producer<T> {
+    /// Returns a [`Future`] that pushes an element into the queue
+    /// as soon as a slot is available.
+    ///
+    /// See [`Producer::push()`].
+    ///
+    /// The future resolves to an error
+    /// (containing the element) if the queue has been closed
+    /// or the [`Consumer`] has been dropped
+    /// (i.e. no slot will ever become available).
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn push_async(&mut self, value: T) -> PushFuture<'_, T> {
+        self.assert_blocking();
+        PushFuture {
+            producer: self,
+            value: Some(value),
+        }
+    }
+
+    /// Returns a [`Future`] that resolves to `n` slots
+    /// (initially containing their [`Default`] value) for writing
+    /// as soon as enough slots are available.
+    ///
+    /// See [`Producer::write_chunk()`].
+    ///
+    /// The future resolves to an error
+    /// (containing the number of available slots)
+    /// if `n` is larger than the capacity of the queue.
+    /// It resolves to [`ChunkError::Closed`] if the queue has been closed
+    /// or if the [`Consumer`] has been dropped (i.e. no more slots will become available).
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_async(&mut self, n: usize) -> WriteChunkFuture<'_, T>
+    where
+        T: Default,
+    {
+        WriteChunkFuture(self.write_chunk_uninit_async(n))
+    }
+
+    /// Returns a [`Future`] that resolves to `n` (uninitialized) slots for writing
+    /// as soon as enough slots are available.
+    ///
+    /// See [`Producer::write_chunk_uninit()`].
+    ///
+    /// The future resolves to an error
+    /// (containing the number of available slots)
+    /// if `n` is larger than the capacity of the queue.
+    /// It resolves to [`ChunkError::Closed`] if the queue has been closed
+    /// or if the [`Consumer`] has been dropped (i.e. no more slots will become available).
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn write_chunk_uninit_async(&mut self, n: usize) -> WriteChunkUninitFuture<'_, T> {
+        self.assert_blocking();
+        WriteChunkUninitFuture {
+            producer: Some(self),
+            n,
+        }
+    }
+}
+
+impl<T> Consumer<T> {
+    /// Returns a [`Future`] that pops an element from the queue
+    /// as soon as one is available.
+    ///
+    /// See [`Consumer::pop()`].
+    ///
+    /// The future resolves to an error if the queue is empty
+    /// and the queue has been closed or the [`Producer`] has been dropped
+    /// (i.e. no more elements will become available).
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn pop_async(&mut self) -> PopFuture<'_, T> {
+        self.assert_blocking();
+        PopFuture { consumer: self }
+    }
+
+    /// Returns a [`Future`] that resolves to `n` slots for reading
+    /// as soon as enough slots are available.
+    ///
+    /// See [`Consumer::read_chunk()`].
+    ///
+    /// The future resolves to an error
+    /// (containing the number of available slots)
+    /// if `n` is larger than the capacity of the queue,
+    /// if the queue has been closed or if the [`Producer`] has been dropped
+    /// (i.e. no more slots will become available).
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    pub fn read_chunk_async(&mut self, n: usize) -> ReadChunkFuture<'_, T> {
+        self.assert_blocking();
+        ReadChunkFuture {
+            consumer: Some(self),
+            n,
+        }
+    }
+}
+
+/// Future returned from [`Producer::push_async()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct PushFuture<'a, T> {
+    producer: &'a mut Producer<T>,
+    value: Option<T>,
+}
+
+// The value is never pinned.
+impl<T> Unpin for PushFuture<'_, T> {}
+
+impl<T> Future for PushFuture<'_, T> {
+    type Output = Result<(), PushError<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let p = &mut *this.producer;
+        match poll_ready(&p.buffer.producer_waker, cx.waker(), || p.push_ready()) {
+            Poll::Ready(()) => {
+                let value = this.value.take().expect("future polled after completion");
+                Poll::Ready(p.push(value))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Future returned from [`Producer::write_chunk_async()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteChunkFuture<'a, T>(WriteChunkUninitFuture<'a, T>);
+
+impl<'a, T> Future for WriteChunkFuture<'a, T>
+where
+    T: Default,
+{
+    type Output = Result<WriteChunk<'a, T>, ChunkError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0)
+            .poll(cx)
+            .map(|result| result.map(WriteChunk::from))
+    }
+}
+
+/// Future returned from [`Producer::write_chunk_uninit_async()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteChunkUninitFuture<'a, T> {
+    /// This is `None` after the future has completed.
+    producer: Option<&'a mut Producer<T>>,
+    n: usize,
+}
+
+impl<'a, T> Future for WriteChunkUninitFuture<'a, T> {
+    type Output = Result<WriteChunkUninit<'a, T>, ChunkError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let n = self.n;
+        let p = self
+            .producer
+            .as_deref()
+            .expect("future polled after completion");
+        match poll_ready(&p.buffer.producer_waker, cx.waker(), || {
+            p.write_chunk_ready(n)
+        }) {
+            Poll::Ready(()) => {
+                let p = self.producer.take().unwrap();
+                Poll::Ready(p.write_chunk_uninit(n))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Future returned from [`Consumer::pop_async()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct PopFuture<'a, T> {
+    consumer: &'a mut Consumer<T>,
+}
+
+impl<T> Future for PopFuture<'_, T> {
+    type Output = Result<T, PopError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let c = &mut *self.get_mut().consumer;
+        match poll_ready(&c.buffer.consumer_waker, cx.waker(), || c.pop_ready()) {
+            Poll::Ready(()) => Poll::Ready(c.pop()),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Future returned from [`Consumer::read_chunk_async()`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadChunkFuture<'a, T> {
+    /// This is `None` after the future has completed.
+    consumer: Option<&'a mut Consumer<T>>,
+    n: usize,
+}
+
+impl<'a, T> Future for ReadChunkFuture<'a, T> {
+    type Output = Result<ReadChunk<'a, T>, ChunkError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let n = self.n;
+        let c = self
+            .consumer
+            .as_deref()
+            .expect("future polled after completion");
+        match poll_ready(&c.buffer.consumer_waker, cx.waker(), || {
+            c.read_chunk_ready(n)
+        }) {
+            Poll::Ready(()) => {
+                let c = self.consumer.take().unwrap();
+                Poll::Ready(c.read_chunk(n))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Returns [`Poll::Ready`] if `ready()` returns `true`.
+///
+/// Otherwise, `task_waker` is registered to be woken when the other side makes progress.
+fn poll_ready(
+    waker: &AtomicWaker,
+    task_waker: &Waker,
+    mut ready: impl FnMut() -> bool,
+) -> Poll<()> {
+    if ready() {
+        return Poll::Ready(());
+    }
+    waker.register(task_waker);
+    // The condition has to be checked again after registering,
+    // otherwise a wakeup might be missed.
+    if ready() {
+        Poll::Ready(())
+    } else {
+        Poll::Pending
+    }
+}
+
+/// The stream of items popped from the queue.
+///
+/// # Panics
+///
+/// Polling the stream panics if blocking has not been enabled for the consumer,
+/// see [`RingBuffer::with_blocking()`].
+#[cfg(feature = "futures")]
+impl<T> futures_core::Stream for Consumer<T> {
+    type Item = T;
+
+    /// Pops the next element from the queue.
+    ///
+    /// The stream ends when the queue is empty
+    /// and it has been closed or the [`Producer`] has been dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let c = self.get_mut();
+        c.assert_blocking();
+        match poll_ready(&c.buffer.consumer_waker, cx.waker(), || c.pop_ready()) {
+            Poll::Ready(()) => Poll::Ready(c.pop().ok()),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.slots(), None)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<T> futures_core::FusedStream for Consumer<T> {
+    fn is_terminated(&self) -> bool {
+        self.buffer.pop_closed() && self.is_empty()
+    }
+}
+
+/// A sink that pushes items into the queue.
+///
+/// # Panics
+///
+/// [`poll_ready()`](futures_sink::Sink::poll_ready) panics if blocking has not been enabled
+/// for the producer, see [`RingBuffer::with_blocking()`].
+#[cfg(feature = "futures")]
+impl<T> futures_sink::Sink<T> for Producer<T> {
+    type Error = PushError<T>;
+
+    /// Waits until a slot is available for writing
+    /// (or until the queue has been closed or the [`Consumer`] has been dropped).
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let p = self.get_mut();
+        p.assert_blocking();
+        poll_ready(&p.buffer.producer_waker, cx.waker(), || p.push_ready()).map(Ok)
+    }
+
+    /// Pushes an element into the queue.
+    ///
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// the element is returned back as an error.
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        self.get_mut().push(item)
+    }
+
+    /// Does nothing, all items are immediately available to be read.
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// Closes the queue, see [`Producer::close()`].
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.get_mut().close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Asynchronous writing of bytes into the queue.
+///
+/// # Panics
+///
+/// Writing panics if blocking has not been enabled for the producer,
+/// see [`RingBuffer::with_blocking()`].
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncWrite for Producer<u8> {
+    /// Writes as many bytes as possible, waiting until at least one slot is available.
+    ///
+    /// If the queue has been closed or the [`Consumer`] has been dropped, an error of kind
+    /// [`BrokenPipe`](std::io::ErrorKind::BrokenPipe) is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the producer,
+    /// see [`RingBuffer::with_blocking()`].
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let p = self.get_mut();
+        p.assert_blocking();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        match poll_ready(&p.buffer.producer_waker, cx.waker(), || p.push_ready()) {
+            // NB: This returns an error if the queue has been closed.
+            Poll::Ready(()) => Poll::Ready(std::io::Write::write(p, buf)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        // Nothing to do here.
+        Poll::Ready(Ok(()))
+    }
+
+    /// Closes the queue, see [`Producer::close()`].
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.get_mut().close();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Asynchronous reading of bytes from the queue.
+///
+/// # Panics
+///
+/// Reading panics if blocking has not been enabled for the consumer,
+/// see [`RingBuffer::with_blocking()`].
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for Consumer<u8> {
+    /// Reads as many bytes as possible, waiting until at least one byte is available.
+    ///
+    /// If the queue is empty and it has been closed or the [`Producer`] has been dropped,
+    /// no bytes are read, which signals the end of the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocking has not been enabled for the consumer,
+    /// see [`RingBuffer::with_blocking()`].
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let c = self.get_mut();
+        c.assert_blocking();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        match poll_ready(&c.buffer.consumer_waker, cx.waker(), || c.pop_ready()) {
+            Poll::Ready(()) => {
+                let mut n = c.slots().min(buf.remaining());
+                // NB: If the queue is empty (and abandoned), n is 0 and nothing is read.
+                let chunk = loop {
+                    match c.read_chunk(n) {
+                        Ok(chunk) => break chunk,
+                        // In an overwriting queue, items might be discarded in the meantime.
+                        Err(ChunkError::TooFewSlots(available)) => n = available,
+                        Err(ChunkError::Closed) => unreachable!("only returned when writing"),
+                    }
+                };
+                let (first, second) = chunk.as_slices();
+                buf.put_slice(first);
+                buf.put_slice(second);
+                chunk.commit_all();
+                Poll::Ready(Ok(()))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! A wait-free channel that only keeps the latest value (a.k.a. triple buffer).
+//!
+//! This is an alternative to a [`RingBuffer`](crate::RingBuffer) for sending "state snapshots"
+//! (e.g. parameter values) from one thread to another,
+//! where the reading side is only interested in the most recent value.
+//!
+//! A [`TripleBuffer`] consists of two parts:
+//! a [`Writer`] for publishing new values and
+//! a [`Reader`] for obtaining the latest published value.
+//! Both can be obtained with [`TripleBuffer::new()`].
+//!
+//! Three slots are allocated on construction.
+//! Publishing a value with [`Writer::write()`] never fails, it replaces
+//! any value that has not yet been seen by the [`Reader`].
+//! [`Reader::read()`] always returns a reference to the newest complete value.
+//! Both operations are *wait-free*, they only involve a single atomic swap
+//! (and [`Reader::read()`] doesn't even need that if there is no new value).
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::latest::TripleBuffer;
+//!
+//! let (mut writer, mut reader) = TripleBuffer::new(0.0f32);
+//!
+//! assert_eq!(*reader.read(), 0.0);
+//! writer.write(0.5);
+//! writer.write(0.75);
+//! assert_eq!(*reader.read(), 0.75);
+//! // The latest value can be read repeatedly:
+//! assert_eq!(*reader.read(), 0.75);
+//! ```
+
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_utils::CachePadded;
+
+/// This bit is set in `TripleBuffer::back` if the back slot contains a value
+/// that has not yet been seen by the [`Reader`].
+const NEW_VALUE: usize = 0b100;
+
+/// Mask for extracting the slot index from `TripleBuffer::back`.
+const INDEX_MASK: usize = 0b011;
+
+/// A wait-free single-writer single-reader channel for the latest value.
+///
+/// New values can be published with a [`Writer`] and read with a [`Reader`],
+/// both of which can be obtained with [`TripleBuffer::new()`].
+///
+/// *See also the [module-level documentation](crate::latest).*
+#[derive(Debug)]
+pub struct TripleBuffer<T> {
+    /// The index of the slot that is currently owned by neither side.
+    ///
+    /// The `NEW_VALUE` bit is set if this slot has been written but not yet read.
+    back: CachePadded<AtomicUsize>,
+
+    /// The three slots holding values.
+    slots: [UnsafeCell<T>; 3],
+}
+
+impl<T> TripleBuffer<T> {
+    /// Creates a `TripleBuffer` holding the given value and returns [`Writer`] and [`Reader`].
+    ///
+    /// The value is cloned into all three slots,
+    /// so that no further allocations are necessary afterwards
+    /// (unless the type `T` does that internally).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::latest::TripleBuffer;
+    ///
+    /// let (writer, mut reader) = TripleBuffer::new([0u8; 4]);
+    /// assert_eq!(*reader.read(), [0, 0, 0, 0]);
+    /// ```
+    #[allow(clippy::new_ret_no_self)]
+    #[must_use]
+    pub fn new(initial: T) -> (Writer<T>, Reader<T>)
+    where
+        T: Clone,
+    {
+        let buffer = Arc::new(TripleBuffer {
+            back: CachePadded::new(AtomicUsize::new(1)),
+            slots: [
+                UnsafeCell::new(initial.clone()),
+                UnsafeCell::new(initial.clone()),
+                UnsafeCell::new(initial),
+            ],
+        });
+        let w = Writer {
+            buffer: buffer.clone(),
+            input: 0,
+        };
+        let r = Reader { buffer, output: 2 };
+        (w, r)
+    }
+}
+
+impl<T> PartialEq for TripleBuffer<T> {
+    /// This method tests for `self` and `other` values to be equal, and is used by `==`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::latest::TripleBuffer;
+    ///
+    /// let (w1, r1) = TripleBuffer::new(0);
+    /// assert_eq!(w1.buffer(), r1.buffer());
+    ///
+    /// let (w2, r2) = TripleBuffer::new(0);
+    /// assert_ne!(w1.buffer(), w2.buffer());
+    /// ```
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other)
+    }
+}
+
+impl<T> Eq for TripleBuffer<T> {}
+
+/// The writing side of a [`TripleBuffer`].
+///
+/// Can be moved between threads,
+/// but references from different threads are not allowed
+/// (i.e. it is [`Send`] but not [`Sync`]).
+///
+/// Can only be created with [`TripleBuffer::new()`]
+/// (together with its counterpart, the [`Reader`]).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Writer<T> {
+    /// A reference to the triple buffer.
+    buffer: Arc<TripleBuffer<T>>,
+
+    /// The index of the slot that is owned by the writer.
+    input: usize,
+}
+
+unsafe impl<T: Send> Send for Writer<T> {}
+
+impl<T> Writer<T> {
+    /// Publishes a new value, replacing the previous one.
+    ///
+    /// If the previous value has not yet been seen by the [`Reader`], it is discarded.
+    /// The value that previously occupied the writer's slot is dropped on this thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::latest::TripleBuffer;
+    ///
+    /// let (mut w, mut r) = TripleBuffer::new(0);
+    ///
+    /// w.write(1);
+    /// w.write(2);
+    /// assert_eq!(*r.read(), 2);
+    /// ```
+    pub fn write(&mut self, value: T) {
+        unsafe {
+            *self.buffer.slots[self.input].get() = value;
+        }
+        let back = self
+            .buffer
+            .back
+            .swap(self.input | NEW_VALUE, Ordering::AcqRel);
+        self.input = back & INDEX_MASK;
+    }
+
+    /// Returns a read-only reference to the triple buffer.
+    pub fn buffer(&self) -> &TripleBuffer<T> {
+        &self.buffer
+    }
+}
+
+/// The reading side of a [`TripleBuffer`].
+///
+/// Can be moved between threads,
+/// but references from different threads are not allowed
+/// (i.e. it is [`Send`] but not [`Sync`]).
+///
+/// Can only be created with [`TripleBuffer::new()`]
+/// (together with its counterpart, the [`Writer`]).
+#[derive(Debug, PartialEq, Eq)]
+pub struct Reader<T> {
+    /// A reference to the triple buffer.
+    buffer: Arc<TripleBuffer<T>>,
+
+    /// The index of the slot that is owned by the reader.
+    output: usize,
+}
+
+unsafe impl<T: Send> Send for Reader<T> {}
+
+impl<T> Reader<T> {
+    /// Returns a reference to the newest complete value.
+    ///
+    /// If no new value has been published since the last call,
+    /// the same value is returned again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::latest::TripleBuffer;
+    ///
+    /// let (mut w, mut r) = TripleBuffer::new(String::new());
+    ///
+    /// w.write("hello".into());
+    /// assert_eq!(r.read(), "hello");
+    /// assert_eq!(r.read(), "hello");
+    /// ```
+    pub fn read(&mut self) -> &T {
+        if self.has_update() {
+            let back = self.buffer.back.swap(self.output, Ordering::AcqRel);
+            self.output = back & INDEX_MASK;
+        }
+        unsafe { &*self.buffer.slots[self.output].get() }
+    }
+
+    /// Returns `true` if a new value has been published since the last call to
+    /// [`Reader::read()`].
+    ///
+    /// Since values can be concurrently published on another thread,
+    /// this might become `true` at any time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::latest::TripleBuffer;
+    ///
+    /// let (mut w, mut r) = TripleBuffer::new(0);
+    ///
+    /// assert!(!r.has_update());
+    /// w.write(1);
+    /// assert!(r.has_update());
+    /// r.read();
+    /// assert!(!r.has_update());
+    /// ```
+    pub fn has_update(&self) -> bool {
+        self.buffer.back.load(Ordering::Relaxed) & NEW_VALUE != 0
+    }
+
+    /// Returns a read-only reference to the triple buffer.
+    pub fn buffer(&self) -> &TripleBuffer<T> {
+        &self.buffer
+    }
+}
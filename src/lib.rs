@@ -6,17 +6,46 @@
 //!
 //! A fixed-capacity buffer is allocated on construction.
 //! After that, no more memory is allocated (unless the type `T` does that internally).
+//! Alternatively, a [`StaticRingBuffer`] doesn't need any heap allocation at all.
 //! Reading from and writing into the ring buffer is *lock-free* and *wait-free*.
 //! All reading and writing functions return immediately.
 //! Attempts to write to a full buffer return an error;
-//! values inside the buffer are *not* overwritten.
+//! values inside the buffer are *not* overwritten
+//! (unless explicitly requested, see [`RingBuffer::new_overwriting()`]).
 //! Attempts to read from an empty buffer return an error as well.
 //! Only a single thread can write into the ring buffer and a single thread
 //! (typically a different one) can read from the ring buffer.
-//! If the queue is empty, there is no way for the reading thread to wait
-//! for new data, other than trying repeatedly until reading succeeds.
-//! Similarly, if the queue is full, there is no way for the writing thread
-//! to wait for newly available space to write to, other than trying repeatedly.
+//! Either side can close the queue with [`Producer::close()`] or [`Consumer::close()`],
+//! after which [`PushError::Closed`] and (once all items have been read) [`PopError::Closed`]
+//! are returned.
+//!
+//! If the queue is empty, the reading thread can either try repeatedly until reading succeeds,
+//! or it can be put to sleep until new data is available,
+//! see [`Consumer::pop_blocking()`] and [`Consumer::read_chunk_blocking()`].
+//! Similarly, if the queue is full, the writing thread can either try repeatedly
+//! or it can wait for newly available space to write to,
+//! see [`Producer::push_blocking()`], [`Producer::write_chunk_blocking()`]
+//! and [`Producer::write_chunk_uninit_blocking()`].
+//! All blocking functions are also available with a timeout or a deadline,
+//! e.g. [`Consumer::pop_timeout()`] and [`Producer::push_deadline()`].
+//! Blocking has to be enabled separately for each side, see [`RingBuffer::with_blocking()`].
+//! The non-blocking side stays wait-free, it only has to wake up the blocked thread
+//! (which is only necessary if the other side is actually waiting).
+//!
+//! In an asynchronous context, the reading and writing functions can be `.await`ed
+//! with [`Consumer::pop_async()`], [`Producer::push_async()`] etc.,
+//! see the [`future`] module.
+//!
+//! If only the most recent value is of interest (e.g. for parameter updates),
+//! the triple buffer in the [`latest`] module can be used instead of a ring buffer.
+//! If the required capacity changes at runtime, the [`resize`] module can be used.
+//! If items must never be rejected, the [`unbounded`] module provides a queue
+//! made of linked ring buffer segments.
+//! To make sure that a ring buffer is never deallocated on a realtime thread,
+//! it can be handed to the [`reclaim`] module.
+//! Items that own heap memory can be sent back to the producer
+//! (instead of being dropped by the consumer) with the [`recycle`] module.
+//! The [`pool`] module provides pre-allocated objects to a realtime thread.
 //!
 //! # Examples
 //!
@@ -48,35 +77,141 @@
 #![warn(rust_2018_idioms)]
 #![deny(missing_docs, missing_debug_implementations)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
 use alloc::sync::Arc;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 use core::cell::Cell;
 use core::fmt;
 use core::marker::PhantomData;
-use core::mem::{ManuallyDrop, MaybeUninit};
+#[cfg(feature = "alloc")]
+use core::mem::ManuallyDrop;
+use core::mem::MaybeUninit;
+use core::ops::Deref;
 use core::sync::atomic::{AtomicUsize, Ordering};
 
-use cache_padded::CachePadded;
+use crossbeam_utils::CachePadded;
 
+#[cfg(feature = "std")]
+mod blocking;
+#[cfg(feature = "alloc")]
+mod builder;
 pub mod chunks;
+#[cfg(feature = "alloc")]
+mod convert;
+#[cfg(feature = "alloc")]
+pub mod future;
+#[cfg(feature = "alloc")]
+pub mod latest;
+#[cfg(all(feature = "mirror", target_os = "linux"))]
+mod mirror;
+#[cfg(all(feature = "mlock", target_os = "linux"))]
+mod mlock;
+#[cfg(feature = "alloc")]
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod reclaim;
+#[cfg(feature = "alloc")]
+pub mod recycle;
+#[cfg(feature = "alloc")]
+pub mod resize;
+#[cfg(all(feature = "shm", target_os = "linux"))]
+pub mod shm;
+mod static_buffer;
+#[cfg(feature = "alloc")]
+mod storage;
+#[cfg(feature = "alloc")]
+pub mod unbounded;
+mod waker;
+
+#[cfg(feature = "alloc")]
+pub use builder::{Builder, BuilderOption};
+#[cfg(feature = "alloc")]
+pub use convert::ReuniteError;
+pub use static_buffer::{BorrowedConsumer, BorrowedProducer, ScopedRingBuffer, StaticRingBuffer};
+#[cfg(feature = "alloc")]
+pub use storage::Storage;
+
+use waker::AtomicWaker;
 
 // This is used in the documentation.
 #[allow(unused_imports)]
 use chunks::WriteChunkUninit;
 
+/// The queue has been closed with [`Producer::close()`] or [`Consumer::close()`].
+const CLOSED: usize = 0b001;
+
+/// The [`Producer`] has been dropped.
+const PRODUCER_DROPPED: usize = 0b010;
+
+/// The [`Consumer`] has been dropped.
+const CONSUMER_DROPPED: usize = 0b100;
+
+/// This bit is set in `head` while the [`Consumer`] is reading from an overwriting queue.
+///
+/// As long as it is set, the [`Producer`] is not allowed to move the head forward.
+const READING: usize = 1 << (usize::BITS - 1);
+
+/// Returns the largest capacity for which positions can be stored in a `usize`.
+///
+/// Positions are in range `0 .. 2 * capacity`,
+/// in an overwriting queue they must not collide with the `READING` bit.
+#[cfg(feature = "alloc")]
+const fn max_capacity(overwriting: bool) -> usize {
+    if overwriting {
+        usize::MAX / 4
+    } else {
+        usize::MAX / 2
+    }
+}
+
+/// Wraps a position from the range `0 .. 2 * capacity` to `0 .. capacity`.
+fn collapse_position(capacity: usize, pos: usize) -> usize {
+    if pos < capacity {
+        pos
+    } else {
+        pos - capacity
+    }
+}
+
+/// Increments a position by going `n` slots forward.
+fn increment(capacity: usize, pos: usize, n: usize) -> usize {
+    let threshold = 2 * capacity - n;
+    if pos < threshold {
+        pos + n
+    } else {
+        pos - threshold
+    }
+}
+
+/// Returns the distance between two positions.
+fn distance(capacity: usize, a: usize, b: usize) -> usize {
+    if a <= b {
+        b - a
+    } else {
+        2 * capacity - a + b
+    }
+}
+
 /// A bounded single-producer single-consumer (SPSC) queue.
 ///
 /// Elements can be written with a [`Producer`] and read with a [`Consumer`],
-/// both of which can be obtained with [`RingBuffer::new()`].
+/// both of which can be obtained with [`RingBuffer::new()`]
+/// (or, without heap allocation, with [`StaticRingBuffer::split()`]
+/// and [`ScopedRingBuffer::split()`]).
 ///
 /// *See also the [crate-level documentation](crate).*
 #[derive(Debug)]
 pub struct RingBuffer<T> {
     /// The head of the queue.
     ///
-    /// This integer is in range `0 .. 2 * capacity`.
+    /// This integer is in range `0 .. 2 * capacity`,
+    /// in an overwriting queue it may additionally have the `READING` bit set.
     head: CachePadded<AtomicUsize>,
 
     /// The tail of the queue.
@@ -90,10 +225,53 @@ pub struct RingBuffer<T> {
     /// The queue capacity.
     capacity: usize,
 
+    /// Custom memory for the slots, see [`RingBuffer::with_storage()`].
+    ///
+    /// If this is `None`, the slots have been allocated with a `Vec<T>`
+    /// (or they are owned by a [`StaticRingBuffer`]).
+    #[cfg(feature = "alloc")]
+    storage: Option<Box<dyn Storage<T>>>,
+
+    /// Whether the queue has been closed and whether the producer/consumer has been dropped.
+    ///
+    /// This is a combination of the flags `CLOSED`, `PRODUCER_DROPPED` and `CONSUMER_DROPPED`.
+    state: AtomicUsize,
+
+    /// Whether the producer may block, see [`RingBuffer::with_blocking()`].
+    producer_blocking: bool,
+
+    /// Whether the consumer may block, see [`RingBuffer::with_blocking()`].
+    consumer_blocking: bool,
+
+    /// Used to wake up the producer while it is blocked on a full queue.
+    producer_waker: CachePadded<AtomicWaker>,
+
+    /// Used to wake up the consumer while it is blocked on an empty queue.
+    consumer_waker: CachePadded<AtomicWaker>,
+
+    /// Whether the producer may discard old items, see [`RingBuffer::new_overwriting()`].
+    overwriting: bool,
+
+    /// Whether the slots are mapped twice in a row, see `RingBuffer::new_mirrored()`.
+    ///
+    /// If this is `true`, all chunks are contiguous.
+    mirrored: bool,
+
+    /// The number of items that have been discarded by [`Producer::force_push()`]
+    /// and [`Producer::write_chunk_overwrite()`].
+    dropped: AtomicUsize,
+
+    /// The hand-off slot used by a [`Reclaimer`](reclaim::Reclaimer).
+    #[cfg(feature = "std")]
+    registration: reclaim::Registration<T>,
+
     /// Indicates that dropping a `RingBuffer<T>` may drop elements of type `T`.
     _marker: PhantomData<T>,
 }
 
+// An owned ring buffer (see `RingBuffer::reunite()`) can be moved to another thread.
+unsafe impl<T: Send> Send for RingBuffer<T> {}
+
 impl<T> RingBuffer<T> {
     /// Creates a `RingBuffer` with the given `capacity` and returns [`Producer`] and [`Consumer`].
     ///
@@ -114,29 +292,292 @@ impl<T> RingBuffer<T> {
     /// let (mut producer, consumer) = RingBuffer::new(100);
     /// assert_eq!(producer.push(0.0f32), Ok(()));
     /// ```
+    #[cfg(feature = "alloc")]
     #[allow(clippy::new_ret_no_self)]
     #[must_use]
     pub fn new(capacity: usize) -> (Producer<T>, Consumer<T>) {
-        let buffer = Arc::new(RingBuffer {
-            head: CachePadded::new(AtomicUsize::new(0)),
-            tail: CachePadded::new(AtomicUsize::new(0)),
-            data_ptr: ManuallyDrop::new(Vec::with_capacity(capacity)).as_mut_ptr(),
+        Self::create(capacity, false, false, false)
+    }
+
+    /// Creates a `RingBuffer` with the given `capacity`, reporting errors instead of panicking.
+    ///
+    /// This is like [`RingBuffer::new()`], but it is useful if the `capacity`
+    /// comes from user input (e.g. a configuration file).
+    ///
+    /// # Errors
+    ///
+    /// If `2 * capacity` doesn't fit into a `usize`,
+    /// [`CreateError::CapacityOverflow`] is returned.
+    /// If the memory for the slots cannot be allocated,
+    /// [`CreateError::AllocError`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::{CreateError, RingBuffer};
+    ///
+    /// let (producer, consumer) = RingBuffer::<f32>::try_new(100)?;
+    /// assert_eq!(producer.buffer().capacity(), 100);
+    ///
+    /// assert_eq!(
+    ///     RingBuffer::<f32>::try_new(usize::MAX).unwrap_err(),
+    ///     CreateError::CapacityOverflow
+    /// );
+    /// # Ok::<(), CreateError>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn try_new(capacity: usize) -> Result<(Producer<T>, Consumer<T>), CreateError> {
+        Self::builder(capacity).build()
+    }
+
+    /// Creates a `RingBuffer` where the [`Producer`] and/or the [`Consumer`] may block.
+    ///
+    /// Blocking is enabled separately for each side,
+    /// because the *other* side has to wake up the blocked thread
+    /// each time it makes progress.
+    /// As long as no thread is actually blocked, this costs a sequentially consistent
+    /// memory fence and an atomic load after each operation on the other side
+    /// (roughly 10 nanoseconds on a typical x86-64 machine, see `benches/blocking.rs`).
+    /// If a thread is actually blocked, it is woken up with a system call.
+    /// Sides that are not allowed to block don't cause any overhead on the other side.
+    ///
+    /// The same applies to the asynchronous functions in the [`future`] module,
+    /// which also have to be enabled with this function.
+    ///
+    /// # Examples
+    ///
+    /// A realtime thread pushes items into the queue and never blocks,
+    /// while the consumer thread sleeps until new items become available:
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use rtrb::{Blocking, RingBuffer};
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::with_blocking(2, Blocking::Consumer);
+    ///
+    /// let consumer_thread = std::thread::spawn(move || {
+    ///     let mut sum = 0;
+    ///     // An error is returned after the producer has been dropped:
+    ///     while let Ok(value) = consumer.pop_blocking() {
+    ///         sum += value;
+    ///     }
+    ///     sum
+    /// });
+    ///
+    /// for i in 1..=10 {
+    ///     while producer.push(i).is_err() {
+    ///         // Do something useful here, like producing more data
+    ///     }
+    /// }
+    /// drop(producer);
+    /// assert_eq!(consumer_thread.join().unwrap(), 55);
+    /// # }
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn with_blocking(capacity: usize, blocking: Blocking) -> (Producer<T>, Consumer<T>) {
+        Self::create(
             capacity,
-            _marker: PhantomData,
-        });
+            blocking != Blocking::Consumer,
+            blocking != Blocking::Producer,
+            false,
+        )
+    }
+
+    /// Creates a `RingBuffer` where the [`Producer`] can overwrite the oldest items.
+    ///
+    /// This is useful if the [`Consumer`] is only interested in the most recent data,
+    /// e.g. for a level meter or an oscilloscope display.
+    /// Instead of failing on a full queue, [`Producer::force_push()`] and
+    /// [`Producer::write_chunk_overwrite()`] discard the oldest items to make room.
+    /// The number of discarded items can be obtained with [`Consumer::dropped_items()`].
+    ///
+    /// Both sides stay wait-free, but the [`Consumer`] has to claim the head of the queue
+    /// with an atomic read-modify-write operation each time it starts reading.
+    /// While it is reading (and while a [`ReadChunk`](chunks::ReadChunk)
+    /// or a reference obtained with [`Consumer::peek()`] is alive),
+    /// the oldest items cannot be discarded and the *new* items are discarded instead.
+    ///
+    /// # Panics
+    ///
+    /// Since one bit of the head position is used for claiming it,
+    /// this panics if `4 * capacity` doesn't fit into a `usize`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::new_overwriting(2);
+    ///
+    /// assert_eq!(producer.force_push(10), None);
+    /// assert_eq!(producer.force_push(20), None);
+    /// assert_eq!(producer.force_push(30), Some(10));
+    /// assert_eq!(consumer.pop(), Ok(20));
+    /// assert_eq!(consumer.pop(), Ok(30));
+    /// assert_eq!(consumer.dropped_items(), 1);
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn new_overwriting(capacity: usize) -> (Producer<T>, Consumer<T>) {
+        Self::create(capacity, false, false, true)
+    }
+
+    /// Creates a `RingBuffer` whose `capacity` is a power of two.
+    ///
+    /// Positions in such a queue are wrapped around with a bit mask
+    /// instead of comparisons and subtractions.
+    /// This is selected at compile time by the [`Handle`] type [`PowerOfTwo`],
+    /// therefore it doesn't cause any overhead in other queues.
+    ///
+    /// Apart from that, the returned [`Producer`] and [`Consumer`] behave exactly like
+    /// the ones returned from [`RingBuffer::new()`].
+    /// Functions that are only available for the default handle type
+    /// (e.g. the blocking functions and [`RingBuffer::reunite()`]) cannot be used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is not a power of two.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::new_power_of_two(4);
+    /// for i in 0..10 {
+    ///     assert_eq!(producer.push(i), Ok(()));
+    ///     assert_eq!(consumer.pop(), Ok(i));
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn new_power_of_two(
+        capacity: usize,
+    ) -> (Producer<T, PowerOfTwo<T>>, Consumer<T, PowerOfTwo<T>>) {
+        assert!(
+            capacity.is_power_of_two(),
+            "capacity must be a power of two"
+        );
+        let buffer = Arc::new(Self::from_raw_parts(
+            ManuallyDrop::new(Vec::with_capacity(capacity)).as_mut_ptr(),
+            capacity,
+            None,
+            false,
+            false,
+            false,
+            false,
+        ));
         let p = Producer {
-            buffer: buffer.clone(),
+            buffer: PowerOfTwo(buffer.clone()),
             cached_head: Cell::new(0),
             cached_tail: Cell::new(0),
         };
         let c = Consumer {
-            buffer,
+            buffer: PowerOfTwo(buffer),
             cached_head: Cell::new(0),
             cached_tail: Cell::new(0),
         };
         (p, c)
     }
 
+    /// Creates a `RingBuffer` that uses the given `storage` for its slots.
+    ///
+    /// The capacity of the queue is [`Storage::capacity()`].
+    /// Once the `RingBuffer` is dropped, all remaining items are dropped and then
+    /// the `storage` itself is dropped (which is where it can release its memory).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::mem::MaybeUninit;
+    /// use rtrb::RingBuffer;
+    ///
+    /// let storage: Box<[MaybeUninit<i32>]> = (0..3).map(|_| MaybeUninit::uninit()).collect();
+    /// let (mut producer, mut consumer) = RingBuffer::with_storage(storage);
+    /// assert_eq!(producer.buffer().capacity(), 3);
+    /// assert_eq!(producer.push(10), Ok(()));
+    /// assert_eq!(consumer.pop(), Ok(10));
+    /// ```
+    ///
+    /// See [`Storage`] for how to provide custom memory.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn with_storage<S>(storage: S) -> (Producer<T>, Consumer<T>)
+    where
+        S: Storage<T> + 'static,
+    {
+        let mut storage: Box<dyn Storage<T>> = Box::new(storage);
+        let capacity = storage.capacity();
+        let data_ptr = storage.as_mut_ptr();
+        Self::from_raw_parts(
+            data_ptr,
+            capacity,
+            Some(storage),
+            false,
+            false,
+            false,
+            false,
+        )
+        .split()
+    }
+
+    #[cfg(feature = "alloc")]
+    fn create(
+        capacity: usize,
+        producer_blocking: bool,
+        consumer_blocking: bool,
+        overwriting: bool,
+    ) -> (Producer<T>, Consumer<T>) {
+        Self::from_raw_parts(
+            ManuallyDrop::new(Vec::with_capacity(capacity)).as_mut_ptr(),
+            capacity,
+            None,
+            producer_blocking,
+            consumer_blocking,
+            overwriting,
+            false,
+        )
+        .split()
+    }
+
+    /// If `storage` is `None`, `data_ptr` must have been obtained from a `Vec<T>`
+    /// with the given `capacity`.
+    ///
+    /// If `mirrored` is `true`, the `capacity` slots after `data_ptr` must refer
+    /// to the same memory as the `capacity` slots starting at `data_ptr`.
+    #[cfg(feature = "alloc")]
+    fn from_raw_parts(
+        data_ptr: *mut T,
+        capacity: usize,
+        storage: Option<Box<dyn Storage<T>>>,
+        producer_blocking: bool,
+        consumer_blocking: bool,
+        overwriting: bool,
+        mirrored: bool,
+    ) -> RingBuffer<T> {
+        assert!(capacity <= max_capacity(overwriting), "capacity overflow");
+        RingBuffer {
+            head: CachePadded::new(AtomicUsize::new(0)),
+            tail: CachePadded::new(AtomicUsize::new(0)),
+            data_ptr,
+            capacity,
+            storage,
+            state: AtomicUsize::new(0),
+            producer_blocking,
+            consumer_blocking,
+            producer_waker: CachePadded::new(AtomicWaker::new()),
+            consumer_waker: CachePadded::new(AtomicWaker::new()),
+            overwriting,
+            mirrored,
+            dropped: AtomicUsize::new(0),
+            #[cfg(feature = "std")]
+            registration: reclaim::Registration::new(),
+            _marker: PhantomData,
+        }
+    }
+
     /// Returns the capacity of the queue.
     ///
     /// # Examples
@@ -155,42 +596,59 @@ impl<T> RingBuffer<T> {
     }
 
     /// Wraps a position from the range `0 .. 2 * capacity` to `0 .. capacity`.
-    fn collapse_position(&self, pos: usize) -> usize {
+    ///
+    /// If `power_of_two` is `true` (see [`Handle::POWER_OF_TWO`]),
+    /// a bit mask is used, which only works if `capacity` is a power of two.
+    /// The same applies to the other functions dealing with positions.
+    fn collapse_position(&self, pos: usize, power_of_two: bool) -> usize {
         debug_assert!(pos == 0 || pos < 2 * self.capacity);
-        if pos < self.capacity {
-            pos
+        if power_of_two {
+            pos & (self.capacity - 1)
+        } else {
+            collapse_position(self.capacity, pos)
+        }
+    }
+
+    /// Returns how many of `n` slots starting at the collapsed position `pos`
+    /// can be accessed without wrapping around.
+    ///
+    /// In a mirrored queue, this is always `n`.
+    fn first_len(&self, pos: usize, n: usize) -> usize {
+        if self.mirrored {
+            n
         } else {
-            pos - self.capacity
+            n.min(self.capacity - pos)
         }
     }
 
     /// Returns a pointer to the slot at position `pos`.
     ///
     /// If `pos == 0 && capacity == 0`, the returned pointer must not be dereferenced!
-    unsafe fn slot_ptr(&self, pos: usize) -> *mut T {
+    unsafe fn slot_ptr(&self, pos: usize, power_of_two: bool) -> *mut T {
         debug_assert!(pos == 0 || pos < 2 * self.capacity);
-        self.data_ptr.add(self.collapse_position(pos))
+        self.data_ptr.add(self.collapse_position(pos, power_of_two))
     }
 
     /// Increments a position by going `n` slots forward.
-    fn increment(&self, pos: usize, n: usize) -> usize {
+    fn increment(&self, pos: usize, n: usize, power_of_two: bool) -> usize {
         debug_assert!(pos == 0 || pos < 2 * self.capacity);
         debug_assert!(n <= self.capacity);
-        let threshold = 2 * self.capacity - n;
-        if pos < threshold {
-            pos + n
+        if power_of_two {
+            (pos + n) & (2 * self.capacity - 1)
         } else {
-            pos - threshold
+            increment(self.capacity, pos, n)
         }
     }
 
     /// Increments a position by going one slot forward.
     ///
-    /// This is more efficient than self.increment(..., 1).
-    fn increment1(&self, pos: usize) -> usize {
+    /// This is more efficient than self.increment(..., 1, power_of_two).
+    fn increment1(&self, pos: usize, power_of_two: bool) -> usize {
         debug_assert_ne!(self.capacity, 0);
         debug_assert!(pos < 2 * self.capacity);
-        if pos < 2 * self.capacity - 1 {
+        if power_of_two {
+            (pos + 1) & (2 * self.capacity - 1)
+        } else if pos < 2 * self.capacity - 1 {
             pos + 1
         } else {
             0
@@ -198,34 +656,104 @@ impl<T> RingBuffer<T> {
     }
 
     /// Returns the distance between two positions.
-    fn distance(&self, a: usize, b: usize) -> usize {
+    fn distance(&self, a: usize, b: usize, power_of_two: bool) -> usize {
         debug_assert!(a == 0 || a < 2 * self.capacity);
         debug_assert!(b == 0 || b < 2 * self.capacity);
-        if a <= b {
-            b - a
+        if power_of_two {
+            b.wrapping_sub(a) & (2 * self.capacity - 1)
         } else {
-            2 * self.capacity - a + b
+            distance(self.capacity, a, b)
         }
     }
-}
 
-impl<T> Drop for RingBuffer<T> {
-    /// Drops all non-empty slots.
-    fn drop(&mut self) {
-        let mut head = self.head.load(Ordering::Relaxed);
-        let tail = self.tail.load(Ordering::Relaxed);
+    /// Loads the head position (without the `READING` bit).
+    fn load_head(&self) -> usize {
+        self.head.load(Ordering::Acquire) & !READING
+    }
+
+    /// Drops all items that are currently in the queue.
+    fn drop_items(&mut self) {
+        let mut head = *self.head.get_mut() & !READING;
+        let tail = *self.tail.get_mut();
 
         // Loop over all slots that hold a value and drop them.
         while head != tail {
             unsafe {
-                self.slot_ptr(head).drop_in_place();
+                self.slot_ptr(head, false).drop_in_place();
             }
-            head = self.increment1(head);
+            head = self.increment1(head, false);
+        }
+        *self.head.get_mut() = head;
+    }
+
+    /// Returns `true` if no more items can be pushed
+    /// (because the queue has been closed or the consumer has been dropped).
+    fn push_closed(&self) -> bool {
+        self.state.load(Ordering::Acquire) & (CLOSED | CONSUMER_DROPPED) != 0
+    }
+
+    /// Returns `true` if no more items will become available for popping
+    /// (because the queue has been closed or the producer has been dropped).
+    ///
+    /// Items that have been pushed before are still available, though.
+    fn pop_closed(&self) -> bool {
+        self.state.load(Ordering::Acquire) & (CLOSED | PRODUCER_DROPPED) != 0
+    }
+
+    /// Sets the given `flag` and wakes up both sides, returns the previous state.
+    fn set_state(&self, flag: usize) -> usize {
+        let state = self.state.fetch_or(flag, Ordering::AcqRel);
+        self.wake_producer();
+        self.wake_consumer();
+        state
+    }
+
+    /// Sets `PRODUCER_DROPPED` or `CONSUMER_DROPPED`.
+    ///
+    /// If the other side has already been dropped, the ring buffer is handed off
+    /// to a [`Reclaimer`](reclaim::Reclaimer) (if it has been registered).
+    fn set_dropped(&self, flag: usize) {
+        #[cfg(not(feature = "std"))]
+        self.set_state(flag);
+        #[cfg(feature = "std")]
+        if self.set_state(flag) & (PRODUCER_DROPPED | CONSUMER_DROPPED) != 0 {
+            self.hand_off();
+        }
+    }
+
+    /// Wakes up the producer if it is blocked.
+    ///
+    /// This has to be called by the consumer after making slots available for writing.
+    fn wake_producer(&self) {
+        if self.producer_blocking {
+            self.producer_waker.wake();
+        }
+    }
+
+    /// Wakes up the consumer if it is blocked.
+    ///
+    /// This has to be called by the producer after making slots available for reading.
+    fn wake_consumer(&self) {
+        if self.consumer_blocking {
+            self.consumer_waker.wake();
         }
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    /// Drops all non-empty slots.
+    fn drop(&mut self) {
+        self.drop_items();
 
         // Finally, deallocate the buffer, but don't run any destructors.
-        unsafe {
-            Vec::from_raw_parts(self.data_ptr, 0, self.capacity);
+        // Custom storage is deallocated when the `storage` field is dropped.
+        // Without an allocator, the buffer is owned by a `StaticRingBuffer`
+        // (which never drops its `RingBuffer`).
+        #[cfg(feature = "alloc")]
+        if self.storage.is_none() {
+            unsafe {
+                Vec::from_raw_parts(self.data_ptr, 0, self.capacity);
+            }
         }
     }
 }
@@ -251,6 +779,57 @@ impl<T> PartialEq for RingBuffer<T> {
 
 impl<T> Eq for RingBuffer<T> {}
 
+/// The reference to a [`RingBuffer`] that is held by its [`Producer`] and [`Consumer`].
+///
+/// This trait is sealed, it is implemented for `Arc<RingBuffer<T>>` (the default),
+/// for [`PowerOfTwo<T>`] and for `&RingBuffer<T>`
+/// (see [`StaticRingBuffer::split()`] and [`ScopedRingBuffer::split()`]).
+pub trait Handle<T>: Deref<Target = RingBuffer<T>> + sealed::Sealed {
+    /// Whether positions are wrapped around with a bit mask,
+    /// see [`RingBuffer::new_power_of_two()`].
+    const POWER_OF_TWO: bool;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+#[cfg(feature = "alloc")]
+impl<T> sealed::Sealed for Arc<RingBuffer<T>> {}
+
+#[cfg(feature = "alloc")]
+impl<T> Handle<T> for Arc<RingBuffer<T>> {
+    const POWER_OF_TWO: bool = false;
+}
+
+impl<T> sealed::Sealed for &RingBuffer<T> {}
+
+impl<T> Handle<T> for &RingBuffer<T> {
+    const POWER_OF_TWO: bool = false;
+}
+
+/// The [`Handle`] used in a queue created with [`RingBuffer::new_power_of_two()`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, PartialEq, Eq)]
+pub struct PowerOfTwo<T>(Arc<RingBuffer<T>>);
+
+#[cfg(feature = "alloc")]
+impl<T> Deref for PowerOfTwo<T> {
+    type Target = RingBuffer<T>;
+
+    fn deref(&self) -> &RingBuffer<T> {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> sealed::Sealed for PowerOfTwo<T> {}
+
+#[cfg(feature = "alloc")]
+impl<T> Handle<T> for PowerOfTwo<T> {
+    const POWER_OF_TWO: bool = true;
+}
+
 /// The producer side of a [`RingBuffer`].
 ///
 /// Can be moved between threads,
@@ -259,6 +838,10 @@ impl<T> Eq for RingBuffer<T> {}
 ///
 /// Can only be created with [`RingBuffer::new()`]
 /// (together with its counterpart, the [`Consumer`]).
+/// The type parameter `B` is the handle to the shared [`RingBuffer`];
+/// by default, it is an [`Arc`],
+/// [`StaticRingBuffer::split()`] and [`ScopedRingBuffer::split()`]
+/// create one holding a plain reference.
 ///
 /// Individual elements can be moved into the ring buffer with [`Producer::push()`],
 /// multiple elements at once can be written with [`Producer::write_chunk()`]
@@ -268,14 +851,19 @@ impl<T> Eq for RingBuffer<T> {}
 /// [`Producer::slots()`].
 ///
 /// When the `Producer` is dropped, [`Consumer::is_abandoned()`] will return `true`.
-/// This can be used as a crude way to communicate to the receiving thread
-/// that no more data will be produced.
+/// To signal that no more data will be produced without dropping the `Producer`,
+/// [`Producer::close()`] can be used.
 /// When the `Producer` is dropped after the [`Consumer`] has already been dropped,
 /// [`RingBuffer::drop()`] will be called, freeing the allocated memory.
 #[derive(Debug, PartialEq, Eq)]
-pub struct Producer<T> {
+pub struct Producer<
+    T,
+    // Without repeating `Target`, `T` would be considered unused.
+    #[cfg(feature = "alloc")] B: Handle<T, Target = RingBuffer<T>> = Arc<RingBuffer<T>>,
+    #[cfg(not(feature = "alloc"))] B: Handle<T, Target = RingBuffer<T>>,
+> {
     /// A reference to the ring buffer.
-    buffer: Arc<RingBuffer<T>>,
+    buffer: B,
 
     /// A copy of `buffer.head` for quick access.
     ///
@@ -288,9 +876,16 @@ pub struct Producer<T> {
     cached_tail: Cell<usize>,
 }
 
-unsafe impl<T: Send> Send for Producer<T> {}
+unsafe impl<T: Send, B: Handle<T>> Send for Producer<T, B> {}
+
+impl<T, B: Handle<T>> Drop for Producer<T, B> {
+    /// Wakes up the [`Consumer`] if it is blocked, because no more data will be produced.
+    fn drop(&mut self) {
+        self.buffer.set_dropped(PRODUCER_DROPPED);
+    }
+}
 
-impl<T> Producer<T> {
+impl<T, B: Handle<T>> Producer<T, B> {
     /// Attempts to push an element into the queue.
     ///
     /// The element is *moved* into the ring buffer and its slot
@@ -298,7 +893,10 @@ impl<T> Producer<T> {
     ///
     /// # Errors
     ///
-    /// If the queue is full, the element is returned back as an error.
+    /// If the queue is full, the element is returned back in [`PushError::Full`].
+    /// If the queue has been closed (see [`Producer::close()`] and [`Consumer::close()`])
+    /// or if the [`Consumer`] has been dropped,
+    /// the element is returned back in [`PushError::Closed`].
     ///
     /// # Examples
     ///
@@ -309,21 +907,107 @@ impl<T> Producer<T> {
     ///
     /// assert_eq!(p.push(10), Ok(()));
     /// assert_eq!(p.push(20), Err(PushError::Full(20)));
+    /// drop(c);
+    /// assert_eq!(p.push(30), Err(PushError::Closed(30)));
     /// ```
     pub fn push(&mut self, value: T) -> Result<(), PushError<T>> {
+        if self.buffer.push_closed() {
+            return Err(PushError::Closed(value));
+        }
         if let Some(tail) = self.next_tail() {
             unsafe {
-                self.buffer.slot_ptr(tail).write(value);
+                self.buffer.slot_ptr(tail, B::POWER_OF_TWO).write(value);
             }
-            let tail = self.buffer.increment1(tail);
+            let tail = self.buffer.increment1(tail, B::POWER_OF_TWO);
             self.buffer.tail.store(tail, Ordering::Release);
             self.cached_tail.set(tail);
+            self.buffer.wake_consumer();
             Ok(())
         } else {
             Err(PushError::Full(value))
         }
     }
 
+    /// Pushes an element into the queue, discarding the oldest element if the queue is full.
+    ///
+    /// This is only allowed if the queue has been created with
+    /// [`RingBuffer::new_overwriting()`].
+    ///
+    /// The discarded element is returned, which allows the caller to decide
+    /// where (and when) it is dropped.
+    /// If the [`Consumer`] is currently reading from a full queue,
+    /// the oldest element cannot be discarded and `value` itself is returned instead.
+    /// In both cases, [`Consumer::dropped_items()`] is incremented.
+    ///
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// `value` is returned without being counted as dropped.
+    ///
+    /// # Panics
+    ///
+    /// This panics if the queue has not been created with [`RingBuffer::new_overwriting()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut p, mut c) = RingBuffer::new_overwriting(1);
+    ///
+    /// assert_eq!(p.force_push(10), None);
+    /// assert_eq!(p.force_push(20), Some(10));
+    /// assert_eq!(c.pop(), Ok(20));
+    /// assert_eq!(c.dropped_items(), 1);
+    /// ```
+    pub fn force_push(&mut self, value: T) -> Option<T> {
+        self.assert_overwriting();
+        if self.buffer.push_closed() {
+            return Some(value);
+        }
+        let mut discarded = None;
+        let tail = match self.next_tail() {
+            Some(tail) => tail,
+            None => {
+                let head = self.buffer.head.load(Ordering::Acquire);
+                // The head can only be moved forward if the consumer is not reading.
+                if head & READING == 0
+                    && self.buffer.capacity != 0
+                    && self
+                        .buffer
+                        .head
+                        .compare_exchange(
+                            head,
+                            self.buffer.increment1(head, B::POWER_OF_TWO),
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                        )
+                        .is_ok()
+                {
+                    self.cached_head
+                        .set(self.buffer.increment1(head, B::POWER_OF_TWO));
+                    // The slot at the old head is the same as the slot at the tail.
+                    discarded = Some(unsafe { self.buffer.slot_ptr(head, B::POWER_OF_TWO).read() });
+                    self.buffer.dropped.fetch_add(1, Ordering::Relaxed);
+                    self.cached_tail.get()
+                } else if let Some(tail) = self.next_tail() {
+                    // The consumer has made room in the meantime.
+                    tail
+                } else {
+                    // The consumer is reading from the full queue.
+                    self.buffer.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Some(value);
+                }
+            }
+        };
+        unsafe {
+            self.buffer.slot_ptr(tail, B::POWER_OF_TWO).write(value);
+        }
+        let tail = self.buffer.increment1(tail, B::POWER_OF_TWO);
+        self.buffer.tail.store(tail, Ordering::Release);
+        self.cached_tail.set(tail);
+        self.buffer.wake_consumer();
+        discarded
+    }
+
     /// Returns the number of slots available for writing.
     ///
     /// Since items can be concurrently consumed on another thread, the actual number
@@ -343,9 +1027,12 @@ impl<T> Producer<T> {
     /// assert_eq!(p.slots(), 1024);
     /// ```
     pub fn slots(&self) -> usize {
-        let head = self.buffer.head.load(Ordering::Acquire);
+        let head = self.buffer.load_head();
         self.cached_head.set(head);
-        self.buffer.capacity - self.buffer.distance(head, self.cached_tail.get())
+        self.buffer.capacity
+            - self
+                .buffer
+                .distance(head, self.cached_tail.get(), B::POWER_OF_TWO)
     }
 
     /// Returns `true` if there are currently no slots available for writing.
@@ -390,10 +1077,13 @@ impl<T> Producer<T> {
 
     /// Returns `true` if the corresponding [`Consumer`] has been destroyed.
     ///
+    /// To find out whether the queue has been closed explicitly,
+    /// use [`Producer::is_closed()`].
+    ///
     /// # Examples
     ///
     /// ```
-    /// use rtrb::RingBuffer;
+    /// use rtrb::{PushError, RingBuffer};
     ///
     /// let (mut p, c) = RingBuffer::new(7);
     /// assert!(!p.is_abandoned());
@@ -401,8 +1091,8 @@ impl<T> Producer<T> {
     /// drop(c);
     /// // The items that are still in the ring buffer are not accessible anymore.
     /// assert!(p.is_abandoned());
-    /// // Even though it's futile, items can still be written:
-    /// assert_eq!(p.push(11), Ok(()));
+    /// // No more items can be written:
+    /// assert_eq!(p.push(11), Err(PushError::Closed(11)));
     /// ```
     ///
     /// Since the consumer can be concurrently dropped on another thread,
@@ -427,7 +1117,43 @@ impl<T> Producer<T> {
     /// }
     /// ```
     pub fn is_abandoned(&self) -> bool {
-        Arc::strong_count(&self.buffer) < 2
+        self.buffer.state.load(Ordering::Acquire) & CONSUMER_DROPPED != 0
+    }
+
+    /// Closes the queue, signaling that no more items will be pushed.
+    ///
+    /// The [`Consumer`] can still pop all items that are left in the queue,
+    /// afterwards it will get [`PopError::Closed`].
+    /// Further attempts to push items will fail with [`PushError::Closed`].
+    ///
+    /// If the [`Consumer`] is blocked (or waiting asynchronously), it is woken up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::{PopError, PushError, RingBuffer};
+    ///
+    /// let (mut p, mut c) = RingBuffer::new(2);
+    /// assert_eq!(p.push(10), Ok(()));
+    /// p.close();
+    /// assert_eq!(p.push(20), Err(PushError::Closed(20)));
+    /// assert!(c.is_closed());
+    /// // Unlike after dropping the producer, the queue is not abandoned:
+    /// assert!(!c.is_abandoned());
+    /// assert_eq!(c.pop(), Ok(10));
+    /// assert_eq!(c.pop(), Err(PopError::Closed));
+    /// ```
+    pub fn close(&mut self) {
+        self.buffer.set_state(CLOSED);
+    }
+
+    /// Returns `true` if the queue has been closed
+    /// with [`Producer::close()`] or [`Consumer::close()`].
+    ///
+    /// Dropping the [`Consumer`] does *not* close the queue,
+    /// see [`Producer::is_abandoned()`].
+    pub fn is_closed(&self) -> bool {
+        self.buffer.state.load(Ordering::Acquire) & CLOSED != 0
     }
 
     /// Returns a read-only reference to the ring buffer.
@@ -443,18 +1169,53 @@ impl<T> Producer<T> {
         let tail = self.cached_tail.get();
 
         // Check if the queue is *possibly* full.
-        if self.buffer.distance(self.cached_head.get(), tail) == self.buffer.capacity {
+        if self
+            .buffer
+            .distance(self.cached_head.get(), tail, B::POWER_OF_TWO)
+            == self.buffer.capacity
+        {
             // Refresh the head ...
-            let head = self.buffer.head.load(Ordering::Acquire);
+            let head = self.buffer.load_head();
             self.cached_head.set(head);
 
             // ... and check if it's *really* full.
-            if self.buffer.distance(head, tail) == self.buffer.capacity {
+            if self.buffer.distance(head, tail, B::POWER_OF_TWO) == self.buffer.capacity {
                 return None;
             }
         }
         Some(tail)
     }
+
+    /// Panics if blocking (or asynchronous waiting) is not enabled for the producer.
+    ///
+    /// See [`RingBuffer::with_blocking()`].
+    #[cfg(feature = "alloc")]
+    fn assert_blocking(&self) {
+        assert!(
+            self.buffer.producer_blocking,
+            "blocking is not enabled for the producer"
+        );
+    }
+
+    /// Panics if the queue has not been created with [`RingBuffer::new_overwriting()`].
+    fn assert_overwriting(&self) {
+        assert!(self.buffer.overwriting, "overwriting is not enabled");
+    }
+
+    /// Returns `true` if a blocked `push()` can stop waiting
+    /// (because a slot is available or because the queue has been closed).
+    #[cfg(feature = "alloc")]
+    fn push_ready(&self) -> bool {
+        !self.is_full() || self.buffer.push_closed()
+    }
+
+    /// Returns `true` if a blocked `write_chunk()` can stop waiting
+    /// (because `n` slots are available, because `n` is larger than the capacity
+    /// or because the queue has been closed).
+    #[cfg(feature = "alloc")]
+    fn write_chunk_ready(&self, n: usize) -> bool {
+        n > self.buffer.capacity || self.slots() >= n || self.buffer.push_closed()
+    }
 }
 
 /// The consumer side of a [`RingBuffer`].
@@ -465,6 +1226,10 @@ impl<T> Producer<T> {
 ///
 /// Can only be created with [`RingBuffer::new()`]
 /// (together with its counterpart, the [`Producer`]).
+/// The type parameter `B` is the handle to the shared [`RingBuffer`];
+/// by default, it is an [`Arc`],
+/// [`StaticRingBuffer::split()`] and [`ScopedRingBuffer::split()`]
+/// create one holding a plain reference.
 ///
 /// Individual elements can be moved out of the ring buffer with [`Consumer::pop()`],
 /// multiple elements at once can be read with [`Consumer::read_chunk()`].
@@ -473,18 +1238,24 @@ impl<T> Producer<T> {
 /// [`Consumer::slots()`].
 ///
 /// When the `Consumer` is dropped, [`Producer::is_abandoned()`] will return `true`.
-/// This can be used as a crude way to communicate to the sending thread
-/// that no more data will be consumed.
+/// To signal that no more data will be consumed without dropping the `Consumer`,
+/// [`Consumer::close()`] can be used.
 /// When the `Consumer` is dropped after the [`Producer`] has already been dropped,
 /// [`RingBuffer::drop()`] will be called, freeing the allocated memory.
 #[derive(Debug, PartialEq, Eq)]
-pub struct Consumer<T> {
+pub struct Consumer<
+    T,
+    // Without repeating `Target`, `T` would be considered unused.
+    #[cfg(feature = "alloc")] B: Handle<T, Target = RingBuffer<T>> = Arc<RingBuffer<T>>,
+    #[cfg(not(feature = "alloc"))] B: Handle<T, Target = RingBuffer<T>>,
+> {
     /// A reference to the ring buffer.
-    buffer: Arc<RingBuffer<T>>,
+    buffer: B,
 
     /// A copy of `buffer.head` for quick access.
     ///
-    /// This value is always in sync with `buffer.head`.
+    /// This value is always in sync with `buffer.head`,
+    /// except in an overwriting queue, where the producer may move the head as well.
     cached_head: Cell<usize>,
 
     /// A copy of `buffer.tail` for quick access.
@@ -493,9 +1264,16 @@ pub struct Consumer<T> {
     cached_tail: Cell<usize>,
 }
 
-unsafe impl<T: Send> Send for Consumer<T> {}
+unsafe impl<T: Send, B: Handle<T>> Send for Consumer<T, B> {}
+
+impl<T, B: Handle<T>> Drop for Consumer<T, B> {
+    /// Wakes up the [`Producer`] if it is blocked, because no more data will be consumed.
+    fn drop(&mut self) {
+        self.buffer.set_dropped(CONSUMER_DROPPED);
+    }
+}
 
-impl<T> Consumer<T> {
+impl<T, B: Handle<T>> Consumer<T, B> {
     /// Attempts to pop an element from the queue.
     ///
     /// The element is *moved* out of the ring buffer and its slot
@@ -503,7 +1281,10 @@ impl<T> Consumer<T> {
     ///
     /// # Errors
     ///
-    /// If the queue is empty, an error is returned.
+    /// If the queue is empty, [`PopError::Empty`] is returned.
+    /// If the queue is empty and it has been closed
+    /// (see [`Producer::close()`] and [`Consumer::close()`])
+    /// or the [`Producer`] has been dropped, [`PopError::Closed`] is returned.
     ///
     /// # Examples
     ///
@@ -515,6 +1296,8 @@ impl<T> Consumer<T> {
     /// assert_eq!(p.push(10), Ok(()));
     /// assert_eq!(c.pop(), Ok(10));
     /// assert_eq!(c.pop(), Err(PopError::Empty));
+    /// drop(p);
+    /// assert_eq!(c.pop(), Err(PopError::Closed));
     /// ```
     ///
     /// To obtain an [`Option<T>`](Option), use [`.ok()`](Result::ok) on the result.
@@ -526,19 +1309,37 @@ impl<T> Consumer<T> {
     /// assert_eq!(c.pop().ok(), Some(20));
     /// ```
     pub fn pop(&mut self) -> Result<T, PopError> {
-        if let Some(head) = self.next_head() {
-            let value = unsafe { self.buffer.slot_ptr(head).read() };
-            let head = self.buffer.increment1(head);
-            self.buffer.head.store(head, Ordering::Release);
-            self.cached_head.set(head);
-            Ok(value)
-        } else {
-            Err(PopError::Empty)
-        }
+        self.claim_head();
+        let head = match self.next_head() {
+            Some(head) => head,
+            // The state has to be checked *before* looking for new items again,
+            // otherwise the last items before closing might be missed.
+            None if self.buffer.pop_closed() => match self.next_head() {
+                Some(head) => head,
+                None => {
+                    self.release_head();
+                    return Err(PopError::Closed);
+                }
+            },
+            None => {
+                self.release_head();
+                return Err(PopError::Empty);
+            }
+        };
+        let value = unsafe { self.buffer.slot_ptr(head, B::POWER_OF_TWO).read() };
+        let head = self.buffer.increment1(head, B::POWER_OF_TWO);
+        self.buffer.head.store(head, Ordering::Release);
+        self.cached_head.set(head);
+        self.buffer.wake_producer();
+        Ok(value)
     }
 
     /// Attempts to read an element from the queue without removing it.
     ///
+    /// In a queue created with [`RingBuffer::new_overwriting()`],
+    /// the oldest element cannot be discarded by the [`Producer`]
+    /// until the next call to [`Consumer::pop()`] or [`Consumer::read_chunk()`].
+    ///
     /// # Errors
     ///
     /// If the queue is empty, an error is returned.
@@ -556,9 +1357,11 @@ impl<T> Consumer<T> {
     /// assert_eq!(c.peek(), Ok(&10));
     /// ```
     pub fn peek(&self) -> Result<&T, PeekError> {
+        self.claim_head();
         if let Some(head) = self.next_head() {
-            Ok(unsafe { &*self.buffer.slot_ptr(head) })
+            Ok(unsafe { &*self.buffer.slot_ptr(head, B::POWER_OF_TWO) })
         } else {
+            self.release_head();
             Err(PeekError::Empty)
         }
     }
@@ -582,9 +1385,13 @@ impl<T> Consumer<T> {
     /// assert_eq!(c.slots(), 0);
     /// ```
     pub fn slots(&self) -> usize {
+        self.refresh_head();
         let tail = self.buffer.tail.load(Ordering::Acquire);
         self.cached_tail.set(tail);
-        self.buffer.distance(self.cached_head.get(), tail)
+        // In an overwriting queue, the head might have been moved after loading it.
+        self.buffer
+            .distance(self.cached_head.get(), tail, B::POWER_OF_TWO)
+            .min(self.buffer.capacity)
     }
 
     /// Returns `true` if there are currently no slots available for reading.
@@ -624,11 +1431,15 @@ impl<T> Consumer<T> {
     /// }
     /// ```
     pub fn is_empty(&self) -> bool {
+        self.refresh_head();
         self.next_head().is_none()
     }
 
     /// Returns `true` if the corresponding [`Producer`] has been destroyed.
     ///
+    /// To find out whether the queue has been closed explicitly,
+    /// use [`Consumer::is_closed()`].
+    ///
     /// # Examples
     ///
     /// ```
@@ -665,7 +1476,63 @@ impl<T> Consumer<T> {
     /// }
     /// ```
     pub fn is_abandoned(&self) -> bool {
-        Arc::strong_count(&self.buffer) < 2
+        self.buffer.state.load(Ordering::Acquire) & PRODUCER_DROPPED != 0
+    }
+
+    /// Closes the queue, signaling that no more items will be consumed.
+    ///
+    /// Further attempts to push items will fail with [`PushError::Closed`].
+    /// Items that are left in the queue can still be popped,
+    /// afterwards [`PopError::Closed`] is returned.
+    ///
+    /// If the [`Producer`] is blocked (or waiting asynchronously), it is woken up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::{PushError, RingBuffer};
+    ///
+    /// let (mut p, mut c) = RingBuffer::new(2);
+    /// c.close();
+    /// assert!(p.is_closed());
+    /// assert!(!p.is_abandoned());
+    /// assert_eq!(p.push(10), Err(PushError::Closed(10)));
+    /// ```
+    pub fn close(&mut self) {
+        self.buffer.set_state(CLOSED);
+    }
+
+    /// Returns `true` if the queue has been closed
+    /// with [`Producer::close()`] or [`Consumer::close()`].
+    ///
+    /// Dropping the [`Producer`] does *not* close the queue,
+    /// see [`Consumer::is_abandoned()`].
+    pub fn is_closed(&self) -> bool {
+        self.buffer.state.load(Ordering::Acquire) & CLOSED != 0
+    }
+
+    /// Returns the number of items that have been discarded so far by
+    /// [`Producer::force_push()`] and [`Producer::write_chunk_overwrite()`].
+    ///
+    /// This is always `0` unless the queue has been created with
+    /// [`RingBuffer::new_overwriting()`].
+    /// The counter wraps around on overflow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new_overwriting(2);
+    ///
+    /// for i in 0..5 {
+    ///     p.force_push(i);
+    /// }
+    /// assert_eq!(c.dropped_items(), 3);
+    /// assert_eq!(c.slots(), 2);
+    /// ```
+    pub fn dropped_items(&self) -> usize {
+        self.buffer.dropped.load(Ordering::Relaxed)
     }
 
     /// Returns a read-only reference to the ring buffer.
@@ -693,6 +1560,74 @@ impl<T> Consumer<T> {
         }
         Some(head)
     }
+
+    /// Claims the head of an overwriting queue before reading from it.
+    ///
+    /// This sets the `READING` bit, which prevents the producer from discarding
+    /// the oldest items. The bit is cleared when the head is stored again.
+    fn claim_head(&self) {
+        if self.buffer.overwriting {
+            let head = self.buffer.head.fetch_or(READING, Ordering::Acquire) & !READING;
+            self.update_head(head);
+        }
+    }
+
+    /// Clears the `READING` bit without moving the head.
+    fn release_head(&self) {
+        if self.buffer.overwriting {
+            // The producer doesn't modify the head while the bit is set.
+            self.buffer
+                .head
+                .store(self.cached_head.get(), Ordering::Release);
+        }
+    }
+
+    /// Re-synchronizes the cached head, which may have been moved by the producer
+    /// of an overwriting queue.
+    fn refresh_head(&self) {
+        if self.buffer.overwriting {
+            self.update_head(self.buffer.load_head());
+        }
+    }
+
+    /// Stores a head position that has been loaded from an overwriting queue.
+    ///
+    /// If the producer has moved the head, the cached tail might lag behind it
+    /// (and it would then seem to be more than `capacity` slots ahead),
+    /// therefore it has to be reloaded.
+    fn update_head(&self, head: usize) {
+        if head != self.cached_head.get() {
+            self.cached_head.set(head);
+            self.cached_tail
+                .set(self.buffer.tail.load(Ordering::Acquire));
+        }
+    }
+
+    /// Panics if blocking (or asynchronous waiting) is not enabled for the consumer.
+    ///
+    /// See [`RingBuffer::with_blocking()`].
+    #[cfg(feature = "alloc")]
+    fn assert_blocking(&self) {
+        assert!(
+            self.buffer.consumer_blocking,
+            "blocking is not enabled for the consumer"
+        );
+    }
+
+    /// Returns `true` if a blocked `pop()` can stop waiting
+    /// (because an item is available or because the queue has been closed).
+    #[cfg(feature = "alloc")]
+    fn pop_ready(&self) -> bool {
+        !self.is_empty() || self.buffer.pop_closed()
+    }
+
+    /// Returns `true` if a blocked `read_chunk()` can stop waiting
+    /// (because `n` slots are available, because `n` is larger than the capacity
+    /// or because the queue has been closed).
+    #[cfg(feature = "alloc")]
+    fn read_chunk_ready(&self, n: usize) -> bool {
+        n > self.buffer.capacity || self.slots() >= n || self.buffer.pop_closed()
+    }
 }
 
 /// Extension trait used to provide a [`copy_to_uninit()`](CopyToUninit::copy_to_uninit)
@@ -708,6 +1643,7 @@ impl<T> Consumer<T> {
 /// ```
 pub trait CopyToUninit<T: Copy> {
     /// Copies contents to a possibly uninitialized slice.
+    #[allow(clippy::mut_from_ref)]
     fn copy_to_uninit(&self, dst: &mut [MaybeUninit<T>]) -> &mut [T];
 }
 
@@ -731,11 +1667,70 @@ impl<T: Copy> CopyToUninit<T> for [T] {
     }
 }
 
+/// Specifies which side(s) of a [`RingBuffer`] are allowed to block.
+///
+/// See [`RingBuffer::with_blocking()`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Blocking {
+    /// Only the [`Producer`] can block (waiting for free slots).
+    Producer,
+    /// Only the [`Consumer`] can block (waiting for available items).
+    Consumer,
+    /// Both the [`Producer`] and the [`Consumer`] can block.
+    Both,
+}
+
+/// Error type for [`RingBuffer::try_new()`] and [`Builder::build()`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CreateError {
+    /// Twice the requested capacity doesn't fit into a `usize`.
+    CapacityOverflow,
+    /// The memory for the slots could not be allocated.
+    AllocError,
+    /// The memory for the slots could not be locked, see `Builder::lock_memory()`.
+    ///
+    /// Contains the raw OS error code,
+    /// which can be turned into an `std::io::Error` with `from_raw_os_error()`.
+    MemoryLock(i32),
+    /// Two options of the [`Builder`] cannot be used together.
+    IncompatibleOptions(BuilderOption, BuilderOption),
+    /// The capacity of the [`Storage`] given to [`Builder::storage()`]
+    /// (contained value) differs from the requested capacity.
+    StorageCapacity(usize),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CreateError {}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for CreateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CreateError::CapacityOverflow => "ring buffer capacity overflow".fmt(f),
+            CreateError::AllocError => "ring buffer allocation failed".fmt(f),
+            CreateError::MemoryLock(code) => {
+                write!(f, "locking ring buffer memory failed (OS error {})", code)
+            }
+            CreateError::IncompatibleOptions(a, b) => {
+                write!(f, "ring buffer options {} and {} are incompatible", a, b)
+            }
+            CreateError::StorageCapacity(capacity) => write!(
+                f,
+                "ring buffer storage has capacity {}, which differs from the requested capacity",
+                capacity
+            ),
+        }
+    }
+}
+
 /// Error type for [`Consumer::pop()`].
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum PopError {
     /// The queue was empty.
     Empty,
+    /// The queue was empty and it has been closed or the [`Producer`] has been dropped.
+    Closed,
 }
 
 #[cfg(feature = "std")]
@@ -745,6 +1740,7 @@ impl fmt::Display for PopError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             PopError::Empty => "empty ring buffer".fmt(f),
+            PopError::Closed => "empty and closed ring buffer".fmt(f),
         }
     }
 }
@@ -772,6 +1768,8 @@ impl fmt::Display for PeekError {
 pub enum PushError<T> {
     /// The queue was full.
     Full(T),
+    /// The queue has been closed or the [`Consumer`] has been dropped.
+    Closed(T),
 }
 
 #[cfg(feature = "std")]
@@ -781,6 +1779,7 @@ impl<T> fmt::Debug for PushError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             PushError::Full(_) => f.pad("Full(_)"),
+            PushError::Closed(_) => f.pad("Closed(_)"),
         }
     }
 }
@@ -789,6 +1788,63 @@ impl<T> fmt::Display for PushError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             PushError::Full(_) => "full ring buffer".fmt(f),
+            PushError::Closed(_) => "closed ring buffer".fmt(f),
+        }
+    }
+}
+
+/// Error type for [`Consumer::pop_timeout()`] and [`Consumer::pop_deadline()`].
+#[cfg(feature = "std")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PopTimeoutError {
+    /// The queue was empty and it has been closed or the [`Producer`] has been dropped.
+    Closed,
+    /// The queue was still empty when the timeout expired.
+    Timeout,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PopTimeoutError {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for PopTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PopTimeoutError::Closed => "empty and closed ring buffer".fmt(f),
+            PopTimeoutError::Timeout => "timed out waiting on empty ring buffer".fmt(f),
+        }
+    }
+}
+
+/// Error type for [`Producer::push_timeout()`] and [`Producer::push_deadline()`].
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum PushTimeoutError<T> {
+    /// The queue has been closed or the [`Consumer`] has been dropped.
+    Closed(T),
+    /// The queue was still full when the timeout expired.
+    Timeout(T),
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for PushTimeoutError<T> {}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Debug for PushTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushTimeoutError::Closed(_) => f.pad("Closed(_)"),
+            PushTimeoutError::Timeout(_) => f.pad("Timeout(_)"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Display for PushTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushTimeoutError::Closed(_) => "closed ring buffer".fmt(f),
+            PushTimeoutError::Timeout(_) => "timed out waiting on full ring buffer".fmt(f),
         }
     }
 }
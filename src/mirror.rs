@@ -0,0 +1,178 @@
+//! Ring buffers whose slots are mapped twice in a row (Linux only).
+
+use core::convert::TryFrom;
+use core::mem::size_of;
+use std::io;
+use std::os::unix::io::RawFd;
+
+use crate::{Consumer, Producer, RingBuffer, Storage};
+
+/// Virtual memory where the same pages are mapped twice, back to back.
+///
+/// This doesn't depend on the item type, which allows it to be `'static`.
+#[derive(Debug)]
+struct Mirror {
+    ptr: *mut u8,
+
+    /// The size of one half in bytes.
+    len: usize,
+
+    /// The number of items that fit into one half.
+    capacity: usize,
+}
+
+// The memory is owned exclusively, items are dropped by the `RingBuffer`.
+unsafe impl Send for Mirror {}
+
+unsafe impl<T> Storage<T> for Mirror {
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr.cast()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Drop for Mirror {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.cast(), 2 * self.len);
+        }
+    }
+}
+
+impl Mirror {
+    /// Maps memory for (at least) `capacity` items of type `T` twice.
+    fn new<T>(capacity: usize) -> io::Result<Self> {
+        let item_size = size_of::<T>();
+        if item_size == 0 || capacity == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "mirrored ring buffer must not have a size of zero",
+            ));
+        }
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if page_size < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let page_size = page_size as usize;
+        // Each half of the mapping must consist of whole pages.
+        let unit = page_size / gcd(page_size, item_size);
+        let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "capacity is too large");
+        let capacity = capacity.checked_add(unit - 1).ok_or_else(too_large)? / unit * unit;
+        let len = capacity
+            .checked_mul(item_size)
+            .filter(|len| len.checked_mul(2).is_some())
+            .ok_or_else(too_large)?;
+        let size = libc::off_t::try_from(len).map_err(|_| too_large())?;
+
+        let fd = unsafe { libc::memfd_create(b"rtrb-mirror\0".as_ptr().cast(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let result = unsafe { map_twice(fd, size, len) };
+        // The mappings stay valid after closing the file descriptor.
+        unsafe { libc::close(fd) };
+        Ok(Mirror {
+            ptr: result?,
+            len,
+            capacity,
+        })
+    }
+}
+
+/// Reserves `2 * len` bytes of address space and maps the file `fd` into both halves.
+unsafe fn map_twice(fd: RawFd, size: libc::off_t, len: usize) -> io::Result<*mut u8> {
+    if libc::ftruncate(fd, size) < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let base = libc::mmap(
+        core::ptr::null_mut(),
+        2 * len,
+        libc::PROT_NONE,
+        libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+        -1,
+        0,
+    );
+    if base == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    let base = base.cast::<u8>();
+    for half in [base, base.add(len)] {
+        let ptr = libc::mmap(
+            half.cast(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED | libc::MAP_FIXED,
+            fd,
+            0,
+        );
+        if ptr == libc::MAP_FAILED {
+            let err = io::Error::last_os_error();
+            libc::munmap(base.cast(), 2 * len);
+            return Err(err);
+        }
+    }
+    Ok(base)
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+impl<T> RingBuffer<T> {
+    /// Creates a `RingBuffer` whose slots are mapped twice in a row in virtual memory.
+    ///
+    /// Since the slot after the last one is the first one again,
+    /// chunks never have to be split in two parts.
+    /// [`WriteChunk::as_mut_slice()`](crate::chunks::WriteChunk::as_mut_slice),
+    /// [`WriteChunkUninit::as_mut_slice()`](crate::chunks::WriteChunkUninit::as_mut_slice)
+    /// and [`ReadChunk::as_slice()`](crate::chunks::ReadChunk::as_slice)
+    /// provide all requested slots as a single contiguous slice
+    /// (and the second slice returned from `as_mut_slices()`/`as_slices()` is always empty).
+    ///
+    /// The memory of both halves has to consist of whole pages,
+    /// therefore the `capacity` is rounded up accordingly
+    /// (use [`RingBuffer::capacity()`] to get the actual value).
+    ///
+    /// This is only available on Linux, with the `mirror` feature.
+    ///
+    /// # Errors
+    ///
+    /// If `capacity` is `0` or if `T` is a zero-sized type, or if the capacity is too large,
+    /// an error of kind [`io::ErrorKind::InvalidInput`] is returned.
+    /// Errors from the system calls are returned as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::<f32>::new_mirrored(1000)?;
+    /// assert!(producer.buffer().capacity() >= 1000);
+    /// assert_eq!(producer.push(0.5), Ok(()));
+    /// assert_eq!(consumer.pop(), Ok(0.5));
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn new_mirrored(capacity: usize) -> io::Result<(Producer<T>, Consumer<T>)> {
+        let mut mirror = Mirror::new::<T>(capacity)?;
+        let capacity = mirror.capacity;
+        let data_ptr = Storage::<T>::as_mut_ptr(&mut mirror);
+        Ok(Self::from_raw_parts(
+            data_ptr,
+            capacity,
+            Some(Box::new(mirror)),
+            false,
+            false,
+            false,
+            true,
+        )
+        .split())
+    }
+}
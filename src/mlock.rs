@@ -0,0 +1,81 @@
+//! Slots that are locked into physical memory (Linux only).
+
+use alloc::alloc::{alloc, dealloc, Layout};
+use std::io;
+
+use crate::{CreateError, Storage};
+
+/// Heap memory for the slots, locked with `mlock()` until it is dropped.
+///
+/// The memory consists of whole pages, which are not shared with other allocations
+/// (otherwise `munlock()` would also unlock their memory).
+///
+/// This doesn't depend on the item type, which allows it to be `'static`.
+#[derive(Debug)]
+pub(crate) struct LockedSlots {
+    ptr: *mut u8,
+    layout: Layout,
+    capacity: usize,
+}
+
+// The memory is owned exclusively, items are dropped by the `RingBuffer`.
+unsafe impl Send for LockedSlots {}
+
+unsafe impl<T> Storage<T> for LockedSlots {
+    fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr.cast()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Drop for LockedSlots {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe {
+                libc::munlock(self.ptr.cast(), self.layout.size());
+                dealloc(self.ptr, self.layout);
+            }
+        }
+    }
+}
+
+impl LockedSlots {
+    /// Allocates memory for `capacity` items of type `T` and locks it.
+    pub(crate) fn new<T>(capacity: usize) -> Result<Self, CreateError> {
+        let layout = Layout::array::<T>(capacity).map_err(|_| CreateError::CapacityOverflow)?;
+        if layout.size() == 0 {
+            return Ok(LockedSlots {
+                // A dangling but properly aligned pointer, like in an empty `Vec`.
+                ptr: layout.align() as *mut u8,
+                layout,
+                capacity,
+            });
+        }
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let size = layout
+            .size()
+            .checked_add(page_size - 1)
+            .ok_or(CreateError::CapacityOverflow)?
+            / page_size
+            * page_size;
+        let layout = Layout::from_size_align(size, layout.align().max(page_size))
+            .map_err(|_| CreateError::CapacityOverflow)?;
+        let ptr = unsafe { alloc(layout) };
+        if ptr.is_null() {
+            return Err(CreateError::AllocError);
+        }
+        if unsafe { libc::mlock(ptr.cast(), layout.size()) } != 0 {
+            let code = io::Error::last_os_error().raw_os_error().unwrap_or(0);
+            unsafe { dealloc(ptr, layout) };
+            return Err(CreateError::MemoryLock(code));
+        }
+        Ok(LockedSlots {
+            ptr,
+            layout,
+            capacity,
+        })
+    }
+}
@@ -0,0 +1,246 @@
+//! An object pool for realtime code, built on two ring buffers.
+//!
+//! A pool consists of two parts, which can be obtained with [`Pool::new()`]:
+//! a [`Pool`] for acquiring and releasing objects on the realtime thread and
+//! a [`Manager`] for bringing released objects back into circulation on another thread.
+//!
+//! All objects are created by [`Pool::new()`] and sent to the [`Pool`]
+//! through a ring buffer.
+//! [`Pool::release()`] sends objects back to the [`Manager`] through a second ring buffer,
+//! [`Manager::refill()`] (optionally resetting them with [`Manager::refill_with()`])
+//! makes them available again.
+//! Neither [`Pool::acquire()`] nor [`Pool::release()`] allocates or deallocates memory,
+//! and both are wait-free.
+//!
+//! The [`Stats`] show how often the pool has been exhausted and how close it came to it.
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::pool::Pool;
+//!
+//! let (mut manager, mut pool) = Pool::new(2, || Vec::<f32>::with_capacity(256));
+//!
+//! // On the realtime thread:
+//! let mut a = pool.acquire().unwrap();
+//! let b = pool.acquire().unwrap();
+//! assert!(pool.acquire().is_none());
+//! a.push(0.5);
+//! pool.release(a).unwrap();
+//! pool.release(b).unwrap();
+//!
+//! // On another thread:
+//! assert_eq!(manager.refill_with(|v| v.clear()), 2);
+//! assert_eq!(manager.stats().exhausted, 1);
+//!
+//! assert_eq!(pool.acquire(), Some(Vec::new()));
+//! ```
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{PushError, RingBuffer};
+
+/// Counters that are written by the [`Pool`] and can be read by both sides.
+#[derive(Debug)]
+struct Counters {
+    acquired: AtomicUsize,
+    exhausted: AtomicUsize,
+    min_available: AtomicUsize,
+}
+
+impl Counters {
+    fn stats(&self) -> Stats {
+        Stats {
+            acquired: self.acquired.load(Ordering::Relaxed),
+            exhausted: self.exhausted.load(Ordering::Relaxed),
+            min_available: self.min_available.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Usage statistics of a [`Pool`].
+///
+/// Can be obtained with [`Pool::stats()`] or [`Manager::stats()`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of objects that have been handed out by [`Pool::acquire()`].
+    pub acquired: usize,
+
+    /// The number of times [`Pool::acquire()`] failed because no object was available.
+    pub exhausted: usize,
+
+    /// The smallest number of available objects that remained after [`Pool::acquire()`].
+    pub min_available: usize,
+}
+
+/// The realtime side of an object pool.
+///
+/// Can be created with [`Pool::new()`]
+/// (together with its counterpart, the [`Manager`]).
+///
+/// *See also the [module-level documentation](crate::pool).*
+#[derive(Debug)]
+pub struct Pool<T> {
+    /// Objects that are available for use.
+    objects: crate::Consumer<T>,
+
+    /// Used to send released objects to the manager.
+    released: crate::Producer<T>,
+
+    counters: Arc<Counters>,
+}
+
+impl<T> Pool<T> {
+    /// Creates `size` objects with `init` and returns [`Manager`] and [`Pool`].
+    ///
+    /// This allocates memory, therefore it should not be called on a realtime thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::pool::Pool;
+    ///
+    /// let (_manager, mut pool) = Pool::new(8, || Box::new([0u8; 64]));
+    /// assert_eq!(pool.available(), 8);
+    /// ```
+    #[allow(clippy::new_ret_no_self)]
+    #[must_use]
+    pub fn new(size: usize, mut init: impl FnMut() -> T) -> (Manager<T>, Pool<T>) {
+        let (mut producer, consumer) = RingBuffer::new(size);
+        let (release_producer, release_consumer) = RingBuffer::new(size);
+        for _ in 0..size {
+            if producer.push(init()).is_err() {
+                unreachable!();
+            }
+        }
+        let counters = Arc::new(Counters {
+            acquired: AtomicUsize::new(0),
+            exhausted: AtomicUsize::new(0),
+            min_available: AtomicUsize::new(size),
+        });
+        let m = Manager {
+            objects: producer,
+            released: release_consumer,
+            counters: counters.clone(),
+        };
+        let p = Pool {
+            objects: consumer,
+            released: release_producer,
+            counters,
+        };
+        (m, p)
+    }
+
+    /// Takes an object out of the pool.
+    ///
+    /// This is wait-free.
+    /// If no object is available, `None` is returned and the exhaustion is counted
+    /// in [`Stats::exhausted`].
+    pub fn acquire(&mut self) -> Option<T> {
+        // This is the only thread writing the counters, so no read-modify-write is needed.
+        match self.objects.pop() {
+            Ok(object) => {
+                let acquired = self.counters.acquired.load(Ordering::Relaxed);
+                self.counters
+                    .acquired
+                    .store(acquired.wrapping_add(1), Ordering::Relaxed);
+                let available = self.objects.slots();
+                if available < self.counters.min_available.load(Ordering::Relaxed) {
+                    self.counters
+                        .min_available
+                        .store(available, Ordering::Relaxed);
+                }
+                Some(object)
+            }
+            Err(_) => {
+                let exhausted = self.counters.exhausted.load(Ordering::Relaxed);
+                self.counters
+                    .exhausted
+                    .store(exhausted.wrapping_add(1), Ordering::Relaxed);
+                self.counters.min_available.store(0, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Hands an object back to the [`Manager`].
+    ///
+    /// This is wait-free and it doesn't drop anything.
+    /// The object becomes available again after [`Manager::refill()`].
+    ///
+    /// # Errors
+    ///
+    /// If the [`Manager`] has been dropped, the object is returned back
+    /// in [`PushError::Closed`].
+    /// [`PushError::Full`] can only happen if more objects are released than have been acquired.
+    pub fn release(&mut self, object: T) -> Result<(), PushError<T>> {
+        self.released.push(object)
+    }
+
+    /// Returns the number of objects that can currently be acquired.
+    pub fn available(&self) -> usize {
+        self.objects.slots()
+    }
+
+    /// Returns the usage statistics.
+    pub fn stats(&self) -> Stats {
+        self.counters.stats()
+    }
+}
+
+/// The non-realtime side of an object pool.
+///
+/// Can be created with [`Pool::new()`]
+/// (together with its counterpart, the [`Pool`]).
+///
+/// *See also the [module-level documentation](crate::pool).*
+#[derive(Debug)]
+pub struct Manager<T> {
+    /// Used to make objects available to the pool.
+    objects: crate::Producer<T>,
+
+    /// Objects that have been released by the pool.
+    released: crate::Consumer<T>,
+
+    counters: Arc<Counters>,
+}
+
+impl<T> Manager<T> {
+    /// Makes all released objects available again.
+    ///
+    /// Returns the number of objects that have been moved back into the pool.
+    /// If the [`Pool`] has been dropped, the released objects are dropped instead.
+    pub fn refill(&mut self) -> usize {
+        self.refill_with(|_| {})
+    }
+
+    /// Like [`Manager::refill()`], but calls `reset` on each object beforehand.
+    pub fn refill_with(&mut self, mut reset: impl FnMut(&mut T)) -> usize {
+        let mut count = 0;
+        while let Ok(mut object) = self.released.pop() {
+            reset(&mut object);
+            // There are never more objects than slots, so this can only fail
+            // if the pool is gone, in which case the object is dropped.
+            if self.objects.push(object).is_ok() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns the number of released objects that are waiting for [`Manager::refill()`].
+    pub fn released(&self) -> usize {
+        self.released.slots()
+    }
+
+    /// Returns the usage statistics.
+    pub fn stats(&self) -> Stats {
+        self.counters.stats()
+    }
+
+    /// Returns `true` if the [`Pool`] has been dropped.
+    pub fn is_abandoned(&self) -> bool {
+        self.objects.is_abandoned()
+    }
+}
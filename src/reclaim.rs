@@ -0,0 +1,372 @@
+//! Deferred deallocation, so that ring buffers are never freed on a realtime thread.
+//!
+//! When the [`Producer`] and the [`Consumer`] of a [`RingBuffer`] have both been dropped,
+//! the ring buffer itself is dropped, which drops the remaining items and frees the slots.
+//! This happens on the thread where the last of the two is dropped,
+//! which might be a realtime thread.
+//!
+//! A ring buffer can be registered with a [`Reclaimer`], which prepares a hand-off slot.
+//! If the last [`Producer`] or [`Consumer`] of a registered ring buffer is dropped
+//! on a thread that has been marked with [`set_realtime_thread()`],
+//! the ring buffer is moved into its hand-off slot instead of being dropped
+//! (which doesn't allocate memory, nor does it wait for a lock).
+//! On all other threads, it is dropped as usual.
+//!
+//! Ring buffers that have been handed off are dropped by [`Reclaimer::reclaim()`],
+//! which is supposed to be called periodically from a non-realtime thread.
+//! [`Reclaimer::spawn()`] starts a collector thread that does this automatically.
+//! Alternatively, a callback can be provided with [`Reclaimer::with_callback()`],
+//! which receives each handed-off ring buffer as [`Garbage`].
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::RingBuffer;
+//! use rtrb::reclaim::{set_realtime_thread, Reclaimer};
+//!
+//! let reclaimer = Reclaimer::new();
+//! let (producer, consumer) = RingBuffer::<Vec<f32>>::new(16);
+//! reclaimer.register(&producer);
+//!
+//! std::thread::spawn(move || {
+//!     set_realtime_thread(true);
+//!     // This doesn't deallocate anything:
+//!     drop((producer, consumer));
+//! }).join().unwrap();
+//!
+//! assert_eq!(reclaimer.pending(), 1);
+//! assert_eq!(reclaimer.reclaim(), 1);
+//! assert_eq!(reclaimer.pending(), 0);
+//! ```
+
+use std::boxed::Box;
+use std::cell::Cell;
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::{Consumer, Producer, RingBuffer};
+
+std::thread_local! {
+    static REALTIME: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as realtime thread (or unmarks it).
+///
+/// If the last [`Producer`] or [`Consumer`] of a ring buffer that has been registered
+/// with a [`Reclaimer`] is dropped on a realtime thread,
+/// the ring buffer is handed off instead of being dropped.
+///
+/// # Examples
+///
+/// ```
+/// use rtrb::reclaim::{is_realtime_thread, set_realtime_thread};
+///
+/// assert!(!is_realtime_thread());
+/// set_realtime_thread(true);
+/// assert!(is_realtime_thread());
+/// ```
+pub fn set_realtime_thread(realtime: bool) {
+    REALTIME.with(|flag| flag.set(realtime));
+}
+
+/// Returns `true` if the current thread has been marked with [`set_realtime_thread()`].
+pub fn is_realtime_thread() -> bool {
+    REALTIME.with(Cell::get)
+}
+
+/// The hand-off slot of a ring buffer, see [`Reclaimer::register()`].
+///
+/// This is stored in each `RingBuffer`, it is empty unless the ring buffer is registered.
+pub(crate) struct Registration<T> {
+    /// Null or a pointer obtained with `Arc::into_raw()`.
+    entry: AtomicPtr<Entry<T>>,
+}
+
+impl<T> Registration<T> {
+    pub(crate) const fn new() -> Self {
+        Registration {
+            entry: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Registration<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registration")
+            .field("registered", &!self.entry.load(Ordering::Relaxed).is_null())
+            .finish()
+    }
+}
+
+impl<T> Drop for Registration<T> {
+    fn drop(&mut self) {
+        let entry = *self.entry.get_mut();
+        if !entry.is_null() {
+            drop(unsafe { Arc::from_raw(entry) });
+        }
+    }
+}
+
+/// The hand-off slot for a registered ring buffer.
+struct Entry<T> {
+    /// Null or a handed-off ring buffer, obtained with `Arc::into_raw()`.
+    buffer: AtomicPtr<RingBuffer<T>>,
+
+    shared: Arc<Shared>,
+
+    /// Type-erases an `Entry`, this is only possible where `T: Send` is known.
+    into_garbage: fn(Arc<Entry<T>>) -> Garbage,
+}
+
+fn into_garbage<T: Send + 'static>(entry: Arc<Entry<T>>) -> Garbage {
+    Garbage(entry)
+}
+
+/// A type-erased hand-off slot.
+trait Reclaimable: Send + Sync {
+    /// Drops the ring buffer if it has been handed off, returns `true` in this case.
+    fn reclaim(&self) -> bool;
+
+    /// Returns `true` if the ring buffer has been handed off but not yet dropped.
+    fn is_pending(&self) -> bool;
+}
+
+impl<T: Send> Reclaimable for Entry<T> {
+    fn reclaim(&self) -> bool {
+        let buffer = self.buffer.swap(ptr::null_mut(), Ordering::Acquire);
+        if buffer.is_null() {
+            return false;
+        }
+        drop(unsafe { Arc::from_raw(buffer) });
+        true
+    }
+
+    fn is_pending(&self) -> bool {
+        !self.buffer.load(Ordering::Relaxed).is_null()
+    }
+}
+
+impl<T> RingBuffer<T> {
+    /// Moves the ring buffer into its hand-off slot (if it is registered
+    /// and this is called on a realtime thread).
+    ///
+    /// This has to be called after the last [`Producer`] or [`Consumer`]
+    /// has been dropped, but before the last reference has been released.
+    pub(crate) fn hand_off(&self) {
+        let entry_ptr = self.registration.entry.load(Ordering::Acquire);
+        if entry_ptr.is_null() || !is_realtime_thread() {
+            return;
+        }
+        // Only ring buffers owned by an `Arc` can be registered.
+        // Incrementing the reference count doesn't allocate.
+        let buffer = unsafe {
+            Arc::increment_strong_count(self);
+            Arc::from_raw(self)
+        };
+        let entry = unsafe { &*entry_ptr };
+        entry
+            .buffer
+            .store(Arc::into_raw(buffer) as *mut _, Ordering::Release);
+        if let Some(callback) = &entry.shared.callback {
+            // If the callback is currently running on another thread, this doesn't wait.
+            // The ring buffer stays in its slot until it is reclaimed.
+            if let Ok(mut callback) = callback.try_lock() {
+                let garbage = unsafe {
+                    Arc::increment_strong_count(entry_ptr);
+                    (entry.into_garbage)(Arc::from_raw(entry_ptr))
+                };
+                callback(garbage);
+            }
+        }
+    }
+}
+
+/// A ring buffer that has been handed off to a callback, see [`Reclaimer::with_callback()`].
+///
+/// Dropping this drops the ring buffer (including its remaining items)
+/// and frees its memory.
+pub struct Garbage(Arc<dyn Reclaimable>);
+
+impl Drop for Garbage {
+    fn drop(&mut self) {
+        self.0.reclaim();
+    }
+}
+
+impl fmt::Debug for Garbage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Garbage(..)")
+    }
+}
+
+type Callback = Mutex<Box<dyn FnMut(Garbage) + Send>>;
+
+#[derive(Default)]
+struct Shared {
+    /// Hand-off slots of all ring buffers that are still alive.
+    entries: Mutex<Vec<Weak<dyn Reclaimable>>>,
+
+    callback: Option<Callback>,
+}
+
+/// Defers dropping ring buffers to a non-realtime thread.
+///
+/// This can be cloned, all clones refer to the same list of ring buffers.
+///
+/// *See also the [module-level documentation](crate::reclaim).*
+#[derive(Clone, Default)]
+pub struct Reclaimer {
+    shared: Arc<Shared>,
+}
+
+impl fmt::Debug for Reclaimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reclaimer")
+            .field("pending", &self.pending())
+            .field("callback", &self.shared.callback.is_some())
+            .finish()
+    }
+}
+
+impl Reclaimer {
+    /// Creates a `Reclaimer` whose handed-off ring buffers are dropped
+    /// by [`Reclaimer::reclaim()`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a `Reclaimer` that passes each handed-off ring buffer to `callback`.
+    ///
+    /// The `callback` is called on the realtime thread,
+    /// it should send the [`Garbage`] to another thread, where it can be dropped
+    /// (e.g. with a separate [`RingBuffer`]).
+    ///
+    /// The `callback` is never called concurrently. If it is already running
+    /// (because ring buffers are dropped on multiple realtime threads at the same time),
+    /// the calling thread doesn't wait for it, the ring buffer stays in its hand-off slot
+    /// instead and can be dropped with [`Reclaimer::reclaim()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    /// use rtrb::reclaim::{set_realtime_thread, Garbage, Reclaimer};
+    ///
+    /// let (mut garbage_producer, mut garbage_consumer) = RingBuffer::<Garbage>::new(8);
+    /// let reclaimer = Reclaimer::with_callback(move |garbage| {
+    ///     // If the queue is full, the ring buffer is dropped on the realtime thread.
+    ///     let _ = garbage_producer.push(garbage);
+    /// });
+    ///
+    /// let (producer, consumer) = RingBuffer::<String>::new(4);
+    /// reclaimer.register(&producer);
+    ///
+    /// std::thread::spawn(move || {
+    ///     set_realtime_thread(true);
+    ///     drop((producer, consumer));
+    /// }).join().unwrap();
+    ///
+    /// // The ring buffer is deallocated here:
+    /// assert!(garbage_consumer.pop().is_ok());
+    /// ```
+    #[must_use]
+    pub fn with_callback<F>(callback: F) -> Self
+    where
+        F: FnMut(Garbage) + Send + 'static,
+    {
+        Reclaimer {
+            shared: Arc::new(Shared {
+                entries: Mutex::default(),
+                callback: Some(Mutex::new(Box::new(callback))),
+            }),
+        }
+    }
+
+    /// Creates a `Reclaimer` and starts a collector thread that calls
+    /// [`Reclaimer::reclaim()`] repeatedly, sleeping for the given `interval` in between.
+    ///
+    /// The thread stops once all clones of the `Reclaimer`
+    /// and all registered ring buffers have been dropped.
+    #[must_use]
+    pub fn spawn(interval: Duration) -> Self {
+        let reclaimer = Self::new();
+        let shared = Arc::downgrade(&reclaimer.shared);
+        std::thread::spawn(move || {
+            while let Some(shared) = shared.upgrade() {
+                Reclaimer { shared }.reclaim();
+                std::thread::sleep(interval);
+            }
+        });
+        reclaimer
+    }
+
+    /// Prepares a hand-off slot for the ring buffer of the given [`Producer`].
+    ///
+    /// If the ring buffer has already been registered, this does nothing.
+    ///
+    /// This locks a mutex and it allocates memory,
+    /// so it should not be called on a realtime thread.
+    pub fn register<T: Send + 'static>(&self, producer: &Producer<T>) {
+        self.register_buffer(producer.buffer());
+    }
+
+    /// Prepares a hand-off slot for the ring buffer of the given [`Consumer`].
+    ///
+    /// See [`Reclaimer::register()`].
+    pub fn register_consumer<T: Send + 'static>(&self, consumer: &Consumer<T>) {
+        self.register_buffer(consumer.buffer());
+    }
+
+    fn register_buffer<T: Send + 'static>(&self, buffer: &RingBuffer<T>) {
+        let entry = Arc::new(Entry {
+            buffer: AtomicPtr::new(ptr::null_mut()),
+            shared: self.shared.clone(),
+            into_garbage: into_garbage::<T>,
+        });
+        let weak: Weak<dyn Reclaimable> = Arc::downgrade(&entry) as _;
+        let entry = Arc::into_raw(entry) as *mut _;
+        if buffer
+            .registration
+            .entry
+            .compare_exchange(ptr::null_mut(), entry, Ordering::AcqRel, Ordering::Relaxed)
+            .is_err()
+        {
+            drop(unsafe { Arc::from_raw(entry) });
+            return;
+        }
+        let mut entries = self.shared.entries.lock().unwrap();
+        entries.retain(|entry| entry.strong_count() != 0);
+        entries.push(weak);
+    }
+
+    /// Drops all registered ring buffers that have been handed off.
+    ///
+    /// Returns the number of dropped ring buffers.
+    pub fn reclaim(&self) -> usize {
+        // The ring buffers are dropped after releasing the lock.
+        self.entries()
+            .iter()
+            .filter(|entry| entry.reclaim())
+            .count()
+    }
+
+    /// Returns the number of ring buffers that have been handed off but not dropped yet.
+    pub fn pending(&self) -> usize {
+        self.entries()
+            .iter()
+            .filter(|entry| entry.is_pending())
+            .count()
+    }
+
+    /// Returns the hand-off slots of all registered ring buffers that are still alive.
+    fn entries(&self) -> Vec<Arc<dyn Reclaimable>> {
+        let mut entries = self.shared.entries.lock().unwrap();
+        entries.retain(|entry| entry.strong_count() != 0);
+        entries.iter().filter_map(Weak::upgrade).collect()
+    }
+}
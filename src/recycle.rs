@@ -0,0 +1,232 @@
+//! A ring buffer with a return path, so that items are never dropped on the consumer's thread.
+//!
+//! A ring buffer with a return path can be created with [`RingBuffer::new_with_return()`],
+//! which returns a [`Producer`] and a [`Consumer`] from this module.
+//!
+//! This is useful if the items own heap memory (e.g. `Box` or `Vec`)
+//! and the consumer runs on a realtime thread, where memory must not be deallocated.
+//! After using an item, the [`Consumer`] hands it back with [`Consumer::recycle()`].
+//! The [`Producer`] obtains it with [`Producer::reclaim()`] and can reuse or drop it.
+//!
+//! Items that have been pushed but not yet reclaimed count against the capacity,
+//! therefore the return queue can hold all of them and recycling never fails
+//! (unless the [`Producer`] has been dropped,
+//! or more items are recycled than have been popped).
+//! The flip side is that items which are popped but never recycled
+//! permanently reduce the number of available slots.
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::{PushError, RingBuffer};
+//!
+//! let (mut producer, mut consumer) = RingBuffer::new_with_return(2);
+//! producer.push(vec![1.0f32; 4]).unwrap();
+//! producer.push(vec![2.0; 4]).unwrap();
+//!
+//! // On the realtime thread:
+//! let buffer = consumer.pop().unwrap();
+//! assert_eq!(buffer[0], 1.0);
+//! consumer.recycle(buffer).unwrap();
+//!
+//! // The popped item is still outstanding:
+//! assert!(matches!(producer.push(vec![]), Err(PushError::Full(_))));
+//!
+//! let mut buffer = producer.reclaim().unwrap();
+//! buffer.fill(3.0);
+//! producer.push(buffer).unwrap();
+//! ```
+
+use crate::{PeekError, PopError, PushError, RingBuffer};
+
+impl<T> RingBuffer<T> {
+    /// Creates a ring buffer with the given `capacity`, together with a return path
+    /// for used items.
+    ///
+    /// See the [`recycle`](crate::recycle) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::new_with_return(1);
+    /// producer.push(Box::new(42)).unwrap();
+    /// let item = consumer.pop().unwrap();
+    /// consumer.recycle(item).unwrap();
+    /// assert_eq!(producer.reclaim(), Ok(Box::new(42)));
+    /// ```
+    #[must_use]
+    pub fn new_with_return(capacity: usize) -> (Producer<T>, Consumer<T>) {
+        let (producer, consumer) = RingBuffer::new(capacity);
+        let (return_producer, return_consumer) = RingBuffer::new(capacity);
+        let p = Producer {
+            producer,
+            returned: return_consumer,
+            outstanding: 0,
+        };
+        let c = Consumer {
+            consumer,
+            returned: return_producer,
+            popped: 0,
+        };
+        (p, c)
+    }
+}
+
+/// The producer side of a ring buffer with a return path.
+///
+/// Can be created with [`RingBuffer::new_with_return()`].
+///
+/// *See also the [module-level documentation](crate::recycle).*
+#[derive(Debug)]
+pub struct Producer<T> {
+    producer: crate::Producer<T>,
+
+    /// Used to receive items that have been recycled by the consumer.
+    returned: crate::Consumer<T>,
+
+    /// The number of items that have been pushed but not yet reclaimed.
+    outstanding: usize,
+}
+
+impl<T> Producer<T> {
+    /// Attempts to push an element into the queue.
+    ///
+    /// # Errors
+    ///
+    /// If there are already as many outstanding items as the capacity allows
+    /// (including the ones waiting to be reclaimed), the element is returned back
+    /// in [`PushError::Full`].
+    /// If the [`Consumer`] has been dropped, it is returned in [`PushError::Closed`].
+    pub fn push(&mut self, value: T) -> Result<(), PushError<T>> {
+        if self.outstanding == self.capacity() {
+            if self.producer.is_abandoned() {
+                return Err(PushError::Closed(value));
+            }
+            return Err(PushError::Full(value));
+        }
+        self.producer.push(value)?;
+        self.outstanding += 1;
+        Ok(())
+    }
+
+    /// Takes back an item that has been recycled by the [`Consumer`].
+    ///
+    /// # Errors
+    ///
+    /// If no recycled items are available, an error is returned.
+    /// [`PopError::Closed`] is only returned once the [`Consumer`] has been dropped
+    /// and all recycled items have been reclaimed.
+    pub fn reclaim(&mut self) -> Result<T, PopError> {
+        let value = self.returned.pop()?;
+        self.outstanding -= 1;
+        Ok(value)
+    }
+
+    /// Returns the number of recycled items that can be reclaimed.
+    pub fn reclaimable(&self) -> usize {
+        self.returned.slots()
+    }
+
+    /// Returns the number of items that can currently be pushed.
+    ///
+    /// This might increase after calling [`Producer::reclaim()`].
+    pub fn slots(&self) -> usize {
+        self.capacity() - self.outstanding
+    }
+
+    /// Returns `true` if no more items can currently be pushed.
+    pub fn is_full(&self) -> bool {
+        self.slots() == 0
+    }
+
+    /// Returns the capacity of the queue.
+    pub fn capacity(&self) -> usize {
+        self.producer.buffer().capacity()
+    }
+
+    /// Returns `true` if the [`Consumer`] has been dropped.
+    pub fn is_abandoned(&self) -> bool {
+        self.producer.is_abandoned()
+    }
+}
+
+/// The consumer side of a ring buffer with a return path.
+///
+/// Can be created with [`RingBuffer::new_with_return()`].
+///
+/// *See also the [module-level documentation](crate::recycle).*
+#[derive(Debug)]
+pub struct Consumer<T> {
+    consumer: crate::Consumer<T>,
+
+    /// Used to hand used items back to the producer.
+    returned: crate::Producer<T>,
+
+    /// The number of items that have been popped but not yet recycled.
+    popped: usize,
+}
+
+impl<T> Consumer<T> {
+    /// Attempts to pop an element from the queue.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::pop()`].
+    pub fn pop(&mut self) -> Result<T, PopError> {
+        let value = self.consumer.pop()?;
+        self.popped += 1;
+        Ok(value)
+    }
+
+    /// Attempts to read an element without removing it.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::peek()`].
+    pub fn peek(&self) -> Result<&T, PeekError> {
+        self.consumer.peek()
+    }
+
+    /// Hands a used item back to the [`Producer`].
+    ///
+    /// This doesn't drop anything and it is wait-free.
+    /// Any item can be recycled (not only the ones that have been popped),
+    /// but the number of recycled items cannot exceed the number of popped items.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Producer`] has been dropped, the item is returned back
+    /// in [`PushError::Closed`].
+    /// If all popped items have already been recycled,
+    /// it is returned back in [`PushError::Full`].
+    pub fn recycle(&mut self, value: T) -> Result<(), PushError<T>> {
+        if self.popped == 0 {
+            if self.returned.is_abandoned() {
+                return Err(PushError::Closed(value));
+            }
+            return Err(PushError::Full(value));
+        }
+        self.returned.push(value)?;
+        self.popped -= 1;
+        Ok(())
+    }
+
+    /// Returns the number of slots available for reading.
+    pub fn slots(&self) -> usize {
+        self.consumer.slots()
+    }
+
+    /// Returns `true` if there are currently no slots available for reading.
+    pub fn is_empty(&self) -> bool {
+        self.consumer.is_empty()
+    }
+
+    /// Returns `true` if the [`Producer`] has been dropped.
+    ///
+    /// Items might still be available for reading.
+    pub fn is_abandoned(&self) -> bool {
+        self.consumer.is_abandoned()
+    }
+}
@@ -0,0 +1,337 @@
+//! A ring buffer whose capacity can be changed while it is in use.
+//!
+//! A resizable ring buffer can be created with [`RingBuffer::new_resizable()`],
+//! which returns a [`Producer`] and a [`Consumer`] from this module.
+//! They wrap a [`crate::Producer`] and a [`crate::Consumer`], respectively.
+//!
+//! To change the capacity, a new ring buffer (a [`Successor`]) is allocated
+//! (typically on a non-realtime thread) and then handed to [`Producer::switch_to()`],
+//! which doesn't allocate and is wait-free.
+//! All following items are written to the new ring buffer.
+//! The [`Consumer`] keeps reading from the old ring buffer until it is empty
+//! and then switches to the new one, so no items are lost or reordered.
+//! The old ring buffer is deallocated on the consumer's thread
+//! (unless the [`Consumer`] is dropped while the switch is happening).
+//!
+//! [`Producer::resize()`] allocates and switches in one go.
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::RingBuffer;
+//! use rtrb::resize::Successor;
+//!
+//! let (mut producer, mut consumer) = RingBuffer::new_resizable(2);
+//! producer.push(1).unwrap();
+//! producer.push(2).unwrap();
+//! assert!(producer.push(3).is_err());
+//!
+//! // This can happen on another thread:
+//! let successor = Successor::new(4);
+//!
+//! producer.switch_to(successor).unwrap();
+//! assert_eq!(producer.capacity(), 4);
+//! producer.push(3).unwrap();
+//!
+//! assert_eq!(consumer.capacity(), 2);
+//! assert_eq!(consumer.pop(), Ok(1));
+//! assert_eq!(consumer.pop(), Ok(2));
+//! assert_eq!(consumer.pop(), Ok(3));
+//! assert_eq!(consumer.capacity(), 4);
+//! ```
+
+use crate::chunks::{ChunkError, ReadChunk, WriteChunkUninit};
+use crate::{PeekError, PopError, PushError, RingBuffer};
+
+/// The connection from a ring buffer to its successor.
+///
+/// Each ring buffer has its own link queue with capacity `1`.
+#[derive(Debug)]
+struct Link<T> {
+    /// The consumer of the next ring buffer.
+    consumer: crate::Consumer<T>,
+
+    /// The link queue of the next ring buffer.
+    next: crate::Consumer<Link<T>>,
+
+    /// The producer of the previous ring buffer, which is dropped by the consumer.
+    previous: Option<crate::Producer<T>>,
+}
+
+/// A pre-allocated ring buffer that can be handed to [`Producer::switch_to()`].
+///
+/// *See also the [module-level documentation](crate::resize).*
+#[derive(Debug)]
+pub struct Successor<T> {
+    producer: crate::Producer<T>,
+    link_producer: crate::Producer<Link<T>>,
+    link: Link<T>,
+}
+
+impl<T> Successor<T> {
+    /// Allocates a ring buffer with the given `capacity`.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let (producer, consumer) = RingBuffer::new(capacity);
+        let (link_producer, next) = RingBuffer::new(1);
+        Successor {
+            producer,
+            link_producer,
+            link: Link {
+                consumer,
+                next,
+                previous: None,
+            },
+        }
+    }
+
+    /// Returns the capacity of the new ring buffer.
+    pub fn capacity(&self) -> usize {
+        self.producer.buffer().capacity()
+    }
+}
+
+impl<T> RingBuffer<T> {
+    /// Creates a ring buffer whose capacity can be changed later.
+    ///
+    /// See the [`resize`](crate::resize) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::new_resizable(1);
+    /// producer.push(1).unwrap();
+    /// producer.resize(2);
+    /// producer.push(2).unwrap();
+    /// producer.push(3).unwrap();
+    /// assert_eq!(consumer.pop(), Ok(1));
+    /// assert_eq!(consumer.pop(), Ok(2));
+    /// assert_eq!(consumer.pop(), Ok(3));
+    /// ```
+    #[must_use]
+    pub fn new_resizable(capacity: usize) -> (Producer<T>, Consumer<T>) {
+        let Successor {
+            producer,
+            link_producer,
+            link,
+        } = Successor::new(capacity);
+        let p = Producer {
+            producer,
+            link: link_producer,
+        };
+        let c = Consumer {
+            consumer: link.consumer,
+            link: link.next,
+        };
+        (p, c)
+    }
+}
+
+/// The producer side of a resizable ring buffer.
+///
+/// Can be created with [`RingBuffer::new_resizable()`].
+///
+/// *See also the [module-level documentation](crate::resize).*
+#[derive(Debug)]
+pub struct Producer<T> {
+    /// The producer of the current ring buffer.
+    producer: crate::Producer<T>,
+
+    /// Used to publish the successor of the current ring buffer.
+    link: crate::Producer<Link<T>>,
+}
+
+impl<T> Producer<T> {
+    /// Attempts to push an element into the current ring buffer.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Producer::push()`].
+    pub fn push(&mut self, value: T) -> Result<(), PushError<T>> {
+        self.producer.push(value)
+    }
+
+    /// Returns `n` (uninitialized) slots of the current ring buffer for writing.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Producer::write_chunk_uninit()`].
+    pub fn write_chunk_uninit(&mut self, n: usize) -> Result<WriteChunkUninit<'_, T>, ChunkError> {
+        self.producer.write_chunk_uninit(n)
+    }
+
+    /// Returns the number of slots available for writing in the current ring buffer.
+    pub fn slots(&self) -> usize {
+        self.producer.slots()
+    }
+
+    /// Returns `true` if there are currently no slots available for writing.
+    pub fn is_full(&self) -> bool {
+        self.producer.is_full()
+    }
+
+    /// Returns the capacity of the current ring buffer.
+    pub fn capacity(&self) -> usize {
+        self.producer.buffer().capacity()
+    }
+
+    /// Returns `true` if the [`Consumer`] has been dropped.
+    pub fn is_abandoned(&self) -> bool {
+        self.producer.is_abandoned()
+    }
+
+    /// Continues writing into the given (empty) ring buffer.
+    ///
+    /// This doesn't allocate any memory and it is wait-free.
+    /// Items that have already been pushed are still read by the [`Consumer`]
+    /// before the items in the new ring buffer.
+    ///
+    /// The old ring buffer is handed to the [`Consumer`], which deallocates it
+    /// once it has read all remaining items.
+    /// However, if the [`Consumer`] is dropped concurrently,
+    /// the last reference to the old ring buffer might still be dropped here.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Consumer`] has been dropped, nothing is changed and
+    /// the `successor` is returned (so that it isn't deallocated here).
+    pub fn switch_to(&mut self, successor: Successor<T>) -> Result<(), Successor<T>> {
+        let Successor {
+            producer,
+            link_producer,
+            mut link,
+        } = successor;
+        // The old producer travels along with the link, so that it is dropped by the consumer.
+        // Dropping it here might deallocate the old ring buffer and
+        // the consumer might mistake the switch for the end of the stream.
+        link.previous = Some(core::mem::replace(&mut self.producer, producer));
+        match self.link.push(link) {
+            Ok(()) => {
+                self.link = link_producer;
+                Ok(())
+            }
+            // Each link queue is used only once, so this can only fail if the consumer is gone.
+            Err(PushError::Full(mut link) | PushError::Closed(mut link)) => {
+                let previous = link.previous.take().unwrap();
+                let producer = core::mem::replace(&mut self.producer, previous);
+                Err(Successor {
+                    producer,
+                    link_producer,
+                    link,
+                })
+            }
+        }
+    }
+
+    /// Allocates a new ring buffer with the given `capacity` and continues writing into it.
+    ///
+    /// Since this allocates memory, it should not be used on a realtime thread,
+    /// see [`Successor::new()`] and [`Producer::switch_to()`] for an alternative.
+    ///
+    /// If the [`Consumer`] has been dropped, the capacity is not changed.
+    pub fn resize(&mut self, capacity: usize) {
+        let _ = self.switch_to(Successor::new(capacity));
+    }
+}
+
+/// The consumer side of a resizable ring buffer.
+///
+/// Can be created with [`RingBuffer::new_resizable()`].
+///
+/// *See also the [module-level documentation](crate::resize).*
+#[derive(Debug)]
+pub struct Consumer<T> {
+    /// The consumer of the current ring buffer.
+    consumer: crate::Consumer<T>,
+
+    /// Used to receive the successor of the current ring buffer.
+    link: crate::Consumer<Link<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Attempts to pop an element, switching to the next ring buffer if necessary.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::pop()`].
+    pub fn pop(&mut self) -> Result<T, PopError> {
+        loop {
+            match self.consumer.pop() {
+                Err(e) if !self.follow() => return Err(e),
+                Err(_) => {}
+                result => return result,
+            }
+        }
+    }
+
+    /// Attempts to read an element without removing it.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::peek()`].
+    pub fn peek(&mut self) -> Result<&T, PeekError> {
+        self.slots();
+        self.consumer.peek()
+    }
+
+    /// Returns `n` slots for reading.
+    ///
+    /// A chunk never spans multiple ring buffers, therefore the items of the current
+    /// ring buffer have to be read before items from its successor can be obtained.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::read_chunk()`].
+    pub fn read_chunk(&mut self, n: usize) -> Result<ReadChunk<'_, T>, ChunkError> {
+        self.slots();
+        self.consumer.read_chunk(n)
+    }
+
+    /// Returns the number of slots available for reading in the current ring buffer.
+    ///
+    /// If the current ring buffer is empty and it has a successor,
+    /// this switches to the successor.
+    pub fn slots(&mut self) -> usize {
+        loop {
+            let slots = self.consumer.slots();
+            if slots != 0 || !self.follow() {
+                return slots;
+            }
+        }
+    }
+
+    /// Returns `true` if there are currently no slots available for reading.
+    pub fn is_empty(&mut self) -> bool {
+        self.slots() == 0
+    }
+
+    /// Returns the capacity of the current ring buffer.
+    ///
+    /// This changes when the [`Consumer`] switches to the next ring buffer,
+    /// which only happens once the current one has been drained.
+    pub fn capacity(&self) -> usize {
+        self.consumer.buffer().capacity()
+    }
+
+    /// Switches to the next ring buffer if the current one is empty and has a successor.
+    ///
+    /// Returns `true` if the switch has happened.
+    fn follow(&mut self) -> bool {
+        // Once the link has been published, no more items are written
+        // into the current ring buffer.
+        if self.link.is_empty() || !self.consumer.is_empty() {
+            return false;
+        }
+        match self.link.pop() {
+            // The producer of the old ring buffer (if any) is dropped here.
+            Ok(Link { consumer, next, .. }) => {
+                self.consumer = consumer;
+                self.link = next;
+                true
+            }
+            Err(_) => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,578 @@
+//! A ring buffer in shared memory for communication between processes (Linux only).
+//!
+//! A [`SharedRingBuffer`] is created with [`SharedRingBuffer::new()`],
+//! which allocates an anonymous shared memory file (using `memfd_create()`)
+//! and maps it into the address space of the current process.
+//! The mapped region starts with a versioned header containing the `head` and `tail`
+//! positions, followed by the slot array.
+//!
+//! Other processes can map the same memory by inheriting it with `fork()`
+//! or by receiving the file descriptor (see [`SharedRingBuffer::as_raw_fd()`]
+//! and [`SharedRingBuffer::from_raw_fd()`]).
+//! Afterwards, one process turns its `SharedRingBuffer` into a [`Producer`]
+//! and another one turns its `SharedRingBuffer` into a [`Consumer`].
+//! Each side can only be attached once.
+//!
+//! Only [`Copy`] types can be transferred, and their memory layout must be the same
+//! in all involved processes (e.g. by using `#[repr(C)]`).
+//! Types containing pointers or references are not meaningful in another process.
+//!
+//! If a process terminates without dropping its [`Producer`] or [`Consumer`],
+//! the other side will *not* notice that it has been abandoned.
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::shm::SharedRingBuffer;
+//!
+//! let buffer = SharedRingBuffer::<f32>::new(1024)?;
+//! let fd = buffer.as_raw_fd();
+//! // In a real application, `fd` would be sent to another process.
+//! let other = unsafe { SharedRingBuffer::<f32>::from_raw_fd(libc::dup(fd))? };
+//!
+//! let mut producer = buffer.into_producer()?;
+//! let mut consumer = other.into_consumer()?;
+//! assert_eq!(producer.push_slice(&[0.5, 0.25]), 2);
+//! assert_eq!(consumer.pop(), Ok(0.5));
+//! assert_eq!(consumer.pop(), Ok(0.25));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::io;
+use std::os::unix::io::RawFd;
+
+use crossbeam_utils::CachePadded;
+
+use crate::{
+    collapse_position, distance, increment, PopError, PushError, CLOSED, CONSUMER_DROPPED,
+    PRODUCER_DROPPED,
+};
+
+/// The first bytes of every shared ring buffer.
+const MAGIC: [u8; 8] = *b"rtrb-shm";
+
+/// The version of the memory layout, has to be incremented on every incompatible change.
+const VERSION: u32 = 1;
+
+/// A [`Producer`] has been attached, see `Header::attached`.
+const PRODUCER_ATTACHED: usize = 0b01;
+
+/// A [`Consumer`] has been attached, see `Header::attached`.
+const CONSUMER_ATTACHED: usize = 0b10;
+
+/// The beginning of the shared memory region, it is followed by the slots.
+#[repr(C)]
+struct Header {
+    /// Must be `MAGIC`.
+    magic: [u8; 8],
+
+    /// Must be `VERSION`.
+    version: u32,
+
+    /// The size of the pointer type (which determines the size of the atomics).
+    pointer_size: u32,
+
+    /// The queue capacity.
+    capacity: u64,
+
+    /// The size of `T`.
+    slot_size: u64,
+
+    /// The alignment of `T`.
+    slot_align: u64,
+
+    /// Whether the [`Producer`] and the [`Consumer`] have been attached.
+    attached: AtomicUsize,
+
+    /// A combination of the flags `CLOSED`, `PRODUCER_DROPPED` and `CONSUMER_DROPPED`.
+    state: AtomicUsize,
+
+    /// The head of the queue, in range `0 .. 2 * capacity`.
+    head: CachePadded<AtomicUsize>,
+
+    /// The tail of the queue, in range `0 .. 2 * capacity`.
+    tail: CachePadded<AtomicUsize>,
+}
+
+/// A memory mapping of a shared memory file.
+#[derive(Debug)]
+struct Mapping {
+    fd: RawFd,
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ptr.is_null() {
+                libc::munmap(self.ptr.cast(), self.len);
+            }
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Returns the offset of the slots and the size of the whole mapping.
+fn layout<T>(capacity: usize) -> io::Result<(usize, usize)> {
+    if align_of::<T>() > 4096 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "alignment of slots is too large",
+        ));
+    }
+    let offset = (size_of::<Header>() + align_of::<T>() - 1) / align_of::<T>() * align_of::<T>();
+    capacity
+        .checked_mul(2)
+        .and_then(|_| capacity.checked_mul(size_of::<T>()))
+        .and_then(|size| size.checked_add(offset))
+        .map(|len| (offset, len))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "capacity is too large"))
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// A ring buffer in shared memory, which is not yet attached as producer or consumer.
+///
+/// *See also the [module-level documentation](crate::shm).*
+#[derive(Debug)]
+pub struct SharedRingBuffer<T> {
+    mapping: Mapping,
+    offset: usize,
+    capacity: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SharedRingBuffer<T> {}
+
+impl<T: Copy> SharedRingBuffer<T> {
+    /// Creates a new shared memory file with the given `capacity` and maps it.
+    ///
+    /// The file descriptor is created with the `close-on-exec` flag.
+    ///
+    /// # Errors
+    ///
+    /// Errors from the system calls are returned.
+    pub fn new(capacity: usize) -> io::Result<Self> {
+        let (offset, len) = layout::<T>(capacity)?;
+        let fd = unsafe { libc::memfd_create(b"rtrb\0".as_ptr().cast(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut mapping = Mapping {
+            fd,
+            ptr: core::ptr::null_mut(),
+            len,
+        };
+        let size = libc::off_t::try_from(len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "capacity is too large"))?;
+        if unsafe { libc::ftruncate(fd, size) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        mapping.ptr = map(fd, len)?;
+        // The file is zero-initialized, which means that all atomics start at 0.
+        unsafe {
+            let header = mapping.ptr.cast::<Header>();
+            (*header).magic = MAGIC;
+            (*header).version = VERSION;
+            (*header).pointer_size = size_of::<usize>() as u32;
+            (*header).capacity = capacity as u64;
+            (*header).slot_size = size_of::<T>() as u64;
+            (*header).slot_align = align_of::<T>() as u64;
+        }
+        Ok(SharedRingBuffer {
+            mapping,
+            offset,
+            capacity,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Maps an existing shared ring buffer, taking ownership of the file descriptor `fd`.
+    ///
+    /// The header is checked for compatibility, including the size and alignment of `T`.
+    ///
+    /// # Errors
+    ///
+    /// If the header doesn't match, an error of kind [`io::ErrorKind::InvalidData`]
+    /// is returned. Errors from the system calls are returned as well.
+    /// In both cases, `fd` is closed.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor which is not used anywhere else
+    /// (it is closed when the `SharedRingBuffer` is dropped).
+    /// It must refer to a shared ring buffer that has been created with
+    /// [`SharedRingBuffer::new()`] for a type with the same memory layout as `T`.
+    ///
+    /// All processes that have access to the shared memory file must be trusted:
+    ///
+    /// * The file must not be truncated while it is mapped,
+    ///   otherwise accessing the slots causes a `SIGBUS` signal.
+    /// * Slots must only be written as specified by the queue protocol,
+    ///   otherwise invalid values of type `T` might be read
+    ///   (and a slot might be written while it is being read).
+    ///
+    /// Invalid `head` and `tail` positions are detected,
+    /// in this case the queue is closed and no slots are accessed.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> io::Result<Self> {
+        let mut mapping = Mapping {
+            fd,
+            ptr: core::ptr::null_mut(),
+            len: 0,
+        };
+        let mut stat = core::mem::MaybeUninit::<libc::stat>::uninit();
+        if libc::fstat(fd, stat.as_mut_ptr()) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let len = usize::try_from(stat.assume_init().st_size)
+            .map_err(|_| invalid_data("invalid file size"))?;
+        if len < size_of::<Header>() {
+            return Err(invalid_data("file is too small for a shared ring buffer"));
+        }
+        mapping.ptr = map(fd, len)?;
+        mapping.len = len;
+        let header = &*mapping.ptr.cast::<Header>();
+        if header.magic != MAGIC {
+            return Err(invalid_data("not a shared ring buffer"));
+        }
+        if header.version != VERSION || header.pointer_size != size_of::<usize>() as u32 {
+            return Err(invalid_data("incompatible shared ring buffer version"));
+        }
+        if header.slot_size != size_of::<T>() as u64 || header.slot_align != align_of::<T>() as u64
+        {
+            return Err(invalid_data("incompatible slot type"));
+        }
+        let capacity =
+            usize::try_from(header.capacity).map_err(|_| invalid_data("invalid capacity"))?;
+        let (offset, expected_len) = layout::<T>(capacity)?;
+        if len != expected_len {
+            return Err(invalid_data("invalid file size"));
+        }
+        Ok(SharedRingBuffer {
+            mapping,
+            offset,
+            capacity,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the file descriptor of the shared memory file.
+    ///
+    /// It stays owned by the `SharedRingBuffer` (and later by the [`Producer`] or [`Consumer`]).
+    pub fn as_raw_fd(&self) -> RawFd {
+        self.mapping.fd
+    }
+
+    /// Returns the capacity of the queue.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Attaches the producer side.
+    ///
+    /// # Errors
+    ///
+    /// If a [`Producer`] has already been attached (in any process),
+    /// an error of kind [`io::ErrorKind::AlreadyExists`] is returned.
+    pub fn into_producer(self) -> io::Result<Producer<T>> {
+        let shared = self.attach(PRODUCER_ATTACHED)?;
+        let tail = shared.load_positions()?.1;
+        Ok(Producer {
+            shared,
+            cached_tail: tail,
+        })
+    }
+
+    /// Attaches the consumer side.
+    ///
+    /// # Errors
+    ///
+    /// If a [`Consumer`] has already been attached (in any process),
+    /// an error of kind [`io::ErrorKind::AlreadyExists`] is returned.
+    pub fn into_consumer(self) -> io::Result<Consumer<T>> {
+        let shared = self.attach(CONSUMER_ATTACHED)?;
+        let head = shared.load_positions()?.0;
+        Ok(Consumer {
+            shared,
+            cached_head: head,
+        })
+    }
+
+    fn attach(self, flag: usize) -> io::Result<Self> {
+        if self.header().attached.fetch_or(flag, Ordering::AcqRel) & flag != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "this side of the shared ring buffer has already been attached",
+            ));
+        }
+        Ok(self)
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { &*self.mapping.ptr.cast::<Header>() }
+    }
+
+    /// Loads the initial positions, see `SharedRingBuffer::is_valid()`.
+    fn load_positions(&self) -> io::Result<(usize, usize)> {
+        let head = self.header().head.load(Ordering::Acquire);
+        let tail = self.header().tail.load(Ordering::Acquire);
+        if !self.is_valid(head, tail) {
+            return Err(invalid_data("invalid head or tail position"));
+        }
+        Ok((head, tail))
+    }
+
+    /// Checks positions that have been loaded from the shared memory.
+    ///
+    /// Only positions in range `0 .. 2 * capacity` are valid,
+    /// and at most `capacity` slots can be in use.
+    /// This can only fail if another process doesn't follow the protocol.
+    fn is_valid(&self, head: usize, tail: usize) -> bool {
+        let in_range = |pos: usize| pos == 0 || pos < 2 * self.capacity;
+        in_range(head) && in_range(tail) && self.distance(head, tail) <= self.capacity
+    }
+
+    /// Returns a pointer to the slot at the (valid) position `pos`.
+    unsafe fn slot_ptr(&self, pos: usize) -> *mut T {
+        let index = collapse_position(self.capacity, pos);
+        self.mapping.ptr.add(self.offset).cast::<T>().add(index)
+    }
+
+    /// Increments a position by going `n` slots forward.
+    fn increment(&self, pos: usize, n: usize) -> usize {
+        increment(self.capacity, pos, n)
+    }
+
+    /// Returns the distance between two positions.
+    fn distance(&self, a: usize, b: usize) -> usize {
+        distance(self.capacity, a, b)
+    }
+
+    /// Sets the given `flag` in the shared state.
+    fn set_state(&self, flag: usize) {
+        self.header().state.fetch_or(flag, Ordering::AcqRel);
+    }
+
+    fn state(&self) -> usize {
+        self.header().state.load(Ordering::Acquire)
+    }
+}
+
+/// Maps the whole file `fd` into memory.
+fn map(fd: RawFd, len: usize) -> io::Result<*mut u8> {
+    let ptr = unsafe {
+        libc::mmap(
+            core::ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(ptr.cast())
+}
+
+/// The producer side of a [`SharedRingBuffer`].
+///
+/// Can be obtained with [`SharedRingBuffer::into_producer()`].
+#[derive(Debug)]
+pub struct Producer<T: Copy> {
+    shared: SharedRingBuffer<T>,
+
+    /// A copy of `tail`, which is always in sync.
+    cached_tail: usize,
+}
+
+unsafe impl<T: Copy + Send> Send for Producer<T> {}
+
+impl<T: Copy> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.shared.set_state(PRODUCER_DROPPED);
+    }
+}
+
+impl<T: Copy> Producer<T> {
+    /// Attempts to push an element into the queue.
+    ///
+    /// # Errors
+    ///
+    /// If the queue is full, the element is returned back in [`PushError::Full`].
+    /// If the queue has been closed or the [`Consumer`] has been dropped,
+    /// the element is returned back in [`PushError::Closed`].
+    pub fn push(&mut self, value: T) -> Result<(), PushError<T>> {
+        if self.shared.state() & (CLOSED | CONSUMER_DROPPED) != 0 {
+            return Err(PushError::Closed(value));
+        }
+        if self.slots() == 0 {
+            return Err(PushError::Full(value));
+        }
+        self.push_slice(&[value]);
+        Ok(())
+    }
+
+    /// Copies as many elements as possible from `values` into the queue.
+    ///
+    /// Returns the number of elements that have been pushed,
+    /// which is `0` if the queue is full or if it has been closed.
+    pub fn push_slice(&mut self, values: &[T]) -> usize {
+        if self.shared.state() & (CLOSED | CONSUMER_DROPPED) != 0 {
+            return 0;
+        }
+        let n = values.len().min(self.slots());
+        let tail = self.cached_tail;
+        for (i, value) in values[..n].iter().enumerate() {
+            unsafe {
+                self.shared
+                    .slot_ptr(self.shared.increment(tail, i))
+                    .write(*value);
+            }
+        }
+        let tail = self.shared.increment(tail, n);
+        self.shared.header().tail.store(tail, Ordering::Release);
+        self.cached_tail = tail;
+        n
+    }
+
+    /// Returns the number of slots available for writing.
+    ///
+    /// If the [`Consumer`] has moved the head to an invalid position,
+    /// the queue is closed and `0` is returned.
+    pub fn slots(&mut self) -> usize {
+        let head = self.shared.header().head.load(Ordering::Acquire);
+        if !self.shared.is_valid(head, self.cached_tail) {
+            self.close();
+            return 0;
+        }
+        self.shared.capacity - self.shared.distance(head, self.cached_tail)
+    }
+
+    /// Returns `true` if there are currently no slots available for writing.
+    pub fn is_full(&mut self) -> bool {
+        self.slots() == 0
+    }
+
+    /// Closes the queue, signaling that no more items will be pushed.
+    pub fn close(&mut self) {
+        self.shared.set_state(CLOSED);
+    }
+
+    /// Returns `true` if the [`Consumer`] has been dropped.
+    pub fn is_abandoned(&self) -> bool {
+        self.shared.state() & CONSUMER_DROPPED != 0
+    }
+
+    /// Returns the capacity of the queue.
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
+
+/// The consumer side of a [`SharedRingBuffer`].
+///
+/// Can be obtained with [`SharedRingBuffer::into_consumer()`].
+#[derive(Debug)]
+pub struct Consumer<T: Copy> {
+    shared: SharedRingBuffer<T>,
+
+    /// A copy of `head`, which is always in sync.
+    cached_head: usize,
+}
+
+unsafe impl<T: Copy + Send> Send for Consumer<T> {}
+
+impl<T: Copy> Drop for Consumer<T> {
+    fn drop(&mut self) {
+        self.shared.set_state(CONSUMER_DROPPED);
+    }
+}
+
+impl<T: Copy> Consumer<T> {
+    /// Attempts to pop an element from the queue.
+    ///
+    /// # Errors
+    ///
+    /// If the queue is empty, [`PopError::Empty`] is returned.
+    /// If the queue is empty and it has been closed or the [`Producer`] has been dropped,
+    /// [`PopError::Closed`] is returned.
+    pub fn pop(&mut self) -> Result<T, PopError> {
+        let mut value = core::mem::MaybeUninit::uninit();
+        match self.pop_into(core::slice::from_mut(&mut value)) {
+            0 if self.shared.state() & (CLOSED | PRODUCER_DROPPED) != 0 => {
+                // The state has to be checked *before* looking for new items again.
+                match self.pop_into(core::slice::from_mut(&mut value)) {
+                    0 => Err(PopError::Closed),
+                    _ => Ok(unsafe { value.assume_init() }),
+                }
+            }
+            0 => Err(PopError::Empty),
+            _ => Ok(unsafe { value.assume_init() }),
+        }
+    }
+
+    /// Copies as many elements as possible from the queue into `buf`.
+    ///
+    /// Returns the number of elements that have been popped.
+    pub fn pop_slice(&mut self, buf: &mut [T]) -> usize {
+        // Only initialized values are written, which is fine for a `T: Copy`.
+        let buf = unsafe { &mut *(buf as *mut [T] as *mut [core::mem::MaybeUninit<T>]) };
+        self.pop_into(buf)
+    }
+
+    fn pop_into(&mut self, buf: &mut [core::mem::MaybeUninit<T>]) -> usize {
+        let n = buf.len().min(self.slots());
+        let head = self.cached_head;
+        for (i, value) in buf[..n].iter_mut().enumerate() {
+            *value = core::mem::MaybeUninit::new(unsafe {
+                self.shared.slot_ptr(self.shared.increment(head, i)).read()
+            });
+        }
+        let head = self.shared.increment(head, n);
+        self.shared.header().head.store(head, Ordering::Release);
+        self.cached_head = head;
+        n
+    }
+
+    /// Returns the number of slots available for reading.
+    ///
+    /// If the [`Producer`] has moved the tail to an invalid position,
+    /// the queue is closed and `0` is returned.
+    pub fn slots(&mut self) -> usize {
+        let tail = self.shared.header().tail.load(Ordering::Acquire);
+        if !self.shared.is_valid(self.cached_head, tail) {
+            self.close();
+            return 0;
+        }
+        self.shared.distance(self.cached_head, tail)
+    }
+
+    /// Returns `true` if there are currently no slots available for reading.
+    pub fn is_empty(&mut self) -> bool {
+        self.slots() == 0
+    }
+
+    /// Closes the queue, signaling that no more items will be consumed.
+    pub fn close(&mut self) {
+        self.shared.set_state(CLOSED);
+    }
+
+    /// Returns `true` if the [`Producer`] has been dropped.
+    pub fn is_abandoned(&self) -> bool {
+        self.shared.state() & PRODUCER_DROPPED != 0
+    }
+
+    /// Returns the capacity of the queue.
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+}
@@ -0,0 +1,287 @@
+//! Ring buffers that don't need heap allocation for their slots (nor for an `Arc`).
+
+use core::cell::Cell;
+use core::marker::PhantomData;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::sync::atomic::AtomicUsize;
+
+use crossbeam_utils::CachePadded;
+
+use crate::waker::AtomicWaker;
+use crate::{Consumer, Producer, RingBuffer};
+
+/// The [`Producer`] returned from [`StaticRingBuffer::split()`] and [`ScopedRingBuffer::split()`].
+pub type BorrowedProducer<'a, T> = Producer<T, &'a RingBuffer<T>>;
+
+/// The [`Consumer`] returned from [`StaticRingBuffer::split()`] and [`ScopedRingBuffer::split()`].
+pub type BorrowedConsumer<'a, T> = Consumer<T, &'a RingBuffer<T>>;
+
+impl<T> RingBuffer<T> {
+    /// Creates a `RingBuffer` for slots that are owned by somebody else.
+    ///
+    /// Since `RingBuffer::drop()` would deallocate the slots,
+    /// the result has to be wrapped in a `ManuallyDrop`.
+    ///
+    /// Like in `RingBuffer::from_raw_parts()`, the capacity is limited
+    /// to keep the index arithmetic from overflowing.
+    const fn borrowed(data_ptr: *mut T, capacity: usize) -> Self {
+        assert!(capacity <= usize::MAX / 2, "capacity overflow");
+        RingBuffer {
+            head: CachePadded::new(AtomicUsize::new(0)),
+            tail: CachePadded::new(AtomicUsize::new(0)),
+            data_ptr,
+            capacity,
+            #[cfg(feature = "alloc")]
+            storage: None,
+            state: AtomicUsize::new(0),
+            producer_blocking: false,
+            consumer_blocking: false,
+            producer_waker: CachePadded::new(AtomicWaker::new()),
+            consumer_waker: CachePadded::new(AtomicWaker::new()),
+            overwriting: false,
+            mirrored: false,
+            dropped: AtomicUsize::new(0),
+            #[cfg(feature = "std")]
+            registration: crate::reclaim::Registration::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a [`Producer`] and a [`Consumer`] holding plain references.
+    ///
+    /// Items that are still in the queue are kept, but the state is reset.
+    fn split_borrowed(&mut self) -> (BorrowedProducer<'_, T>, BorrowedConsumer<'_, T>) {
+        *self.state.get_mut() = 0;
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let p = Producer {
+            buffer: &*self,
+            cached_head: Cell::new(head),
+            cached_tail: Cell::new(tail),
+        };
+        let c = Consumer {
+            buffer: &*self,
+            cached_head: Cell::new(head),
+            cached_tail: Cell::new(tail),
+        };
+        (p, c)
+    }
+}
+
+/// A [`RingBuffer`] with a fixed capacity `N` that doesn't need any heap allocation.
+///
+/// Since its capacity is known at compile time, it can be created in a `const` context,
+/// which means that it can be stored in a `static` (or on the stack).
+/// This way, it can also be used on `no_std` targets without an allocator
+/// (with the `alloc` feature disabled).
+///
+/// [`StaticRingBuffer::split()`] provides a [`Producer`] and a [`Consumer`]
+/// with all the non-blocking operations, including the ones from the [`chunks`](crate::chunks)
+/// module. They hold a plain reference to the underlying [`RingBuffer`],
+/// which is why the `StaticRingBuffer` cannot be moved or dropped while they are alive.
+///
+/// # Examples
+///
+/// ```
+/// use rtrb::{PopError, StaticRingBuffer};
+///
+/// let mut buffer = StaticRingBuffer::<i32, 2>::new();
+/// let (mut producer, mut consumer) = buffer.split();
+///
+/// assert_eq!(producer.push(10), Ok(()));
+/// assert_eq!(producer.push(20), Ok(()));
+/// assert!(producer.push(30).is_err());
+///
+/// drop(producer);
+///
+/// std::thread::scope(|s| {
+///     s.spawn(move || {
+///         assert_eq!(consumer.pop(), Ok(10));
+///         assert_eq!(consumer.pop(), Ok(20));
+///         assert_eq!(consumer.pop(), Err(PopError::Closed));
+///     });
+/// });
+/// ```
+///
+/// Items that have not been read are kept until the next call to `split()`:
+///
+/// ```
+/// use rtrb::StaticRingBuffer;
+///
+/// let mut buffer = StaticRingBuffer::<_, 4>::new();
+/// let (mut producer, consumer) = buffer.split();
+/// producer.push("hello").unwrap();
+/// drop((producer, consumer));
+///
+/// let (_, mut consumer) = buffer.split();
+/// assert_eq!(consumer.pop(), Ok("hello"));
+/// ```
+#[derive(Debug)]
+pub struct StaticRingBuffer<T, const N: usize> {
+    /// The shared state, its `data_ptr` is updated on each call to `split()`.
+    buffer: ManuallyDrop<RingBuffer<T>>,
+
+    /// The slots holding values.
+    storage: MaybeUninit<[T; N]>,
+}
+
+unsafe impl<T: Send, const N: usize> Send for StaticRingBuffer<T, N> {}
+
+// Shared references cannot be used to access the items.
+unsafe impl<T: Send, const N: usize> Sync for StaticRingBuffer<T, N> {}
+
+impl<T, const N: usize> StaticRingBuffer<T, N> {
+    /// Creates an empty `StaticRingBuffer` with capacity `N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::StaticRingBuffer;
+    ///
+    /// static mut BUFFER: StaticRingBuffer<f32, 1024> = StaticRingBuffer::new();
+    /// ```
+    #[must_use]
+    pub const fn new() -> Self {
+        StaticRingBuffer {
+            // The data pointer is only known once `self` doesn't move anymore.
+            buffer: ManuallyDrop::new(RingBuffer::borrowed(core::ptr::null_mut(), N)),
+            storage: MaybeUninit::uninit(),
+        }
+    }
+
+    /// Returns a [`Producer`] and a [`Consumer`] that refer to this ring buffer.
+    ///
+    /// Items that are still in the queue from a previous call are kept,
+    /// but the queue is neither closed nor abandoned anymore.
+    pub fn split(&mut self) -> (BorrowedProducer<'_, T>, BorrowedConsumer<'_, T>) {
+        self.buffer.data_ptr = self.storage.as_mut_ptr().cast();
+        self.buffer.split_borrowed()
+    }
+
+    /// Returns the capacity of the queue (which is `N`).
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> Default for StaticRingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for StaticRingBuffer<T, N> {
+    /// Drops all items that are still in the queue.
+    fn drop(&mut self) {
+        self.buffer.data_ptr = self.storage.as_mut_ptr().cast();
+        self.buffer.drop_items();
+    }
+}
+
+/// A [`RingBuffer`] that uses borrowed memory for its slots.
+///
+/// This can be created with [`RingBuffer::scoped()`].
+/// No heap allocation is needed, neither for the slots nor for an `Arc`.
+/// This is useful for short-lived pipelines, e.g. within [`std::thread::scope()`].
+///
+/// [`ScopedRingBuffer::split()`] provides a [`Producer`] and a [`Consumer`]
+/// with all the non-blocking operations, including the ones from the [`chunks`](crate::chunks)
+/// module and the implementations of `std::io::Write` and `std::io::Read` (for `u8`).
+///
+/// When the `ScopedRingBuffer` is dropped, all remaining items are dropped,
+/// the borrowed memory itself is left untouched.
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+/// use rtrb::{PopError, RingBuffer};
+///
+/// let mut slots = [MaybeUninit::uninit(); 4];
+/// let mut buffer = RingBuffer::scoped(&mut slots);
+/// let (mut producer, mut consumer) = buffer.split();
+///
+/// std::thread::scope(|s| {
+///     s.spawn(move || {
+///         for i in 0..10 {
+///             while producer.push(i).is_err() {}
+///         }
+///     });
+///     let mut sum = 0;
+///     loop {
+///         match consumer.pop() {
+///             Ok(value) => sum += value,
+///             Err(PopError::Empty) => {}
+///             Err(PopError::Closed) => break,
+///         }
+///     }
+///     assert_eq!(sum, 45);
+/// });
+/// ```
+#[derive(Debug)]
+pub struct ScopedRingBuffer<'a, T> {
+    /// The shared state, its `data_ptr` points to the borrowed slots.
+    buffer: ManuallyDrop<RingBuffer<T>>,
+
+    /// Indicates that the slots are mutably borrowed.
+    _slots: PhantomData<&'a mut [MaybeUninit<T>]>,
+}
+
+unsafe impl<T: Send> Send for ScopedRingBuffer<'_, T> {}
+
+// Shared references cannot be used to access the items.
+unsafe impl<T: Send> Sync for ScopedRingBuffer<'_, T> {}
+
+impl<T> RingBuffer<T> {
+    /// Creates a [`ScopedRingBuffer`] that uses the given `slots` as its storage.
+    ///
+    /// The capacity of the queue is the length of `slots`.
+    /// Use [`ScopedRingBuffer::split()`] to obtain a [`Producer`] and a [`Consumer`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `slots` is greater than `usize::MAX / 2`
+    /// (which is only possible for zero-sized types).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::mem::MaybeUninit;
+    /// use rtrb::RingBuffer;
+    ///
+    /// let mut slots = [MaybeUninit::uninit(); 8];
+    /// let mut buffer = RingBuffer::scoped(&mut slots);
+    /// assert_eq!(buffer.capacity(), 8);
+    /// let (mut producer, mut consumer) = buffer.split();
+    /// assert_eq!(producer.push(1.0f32), Ok(()));
+    /// assert_eq!(consumer.pop(), Ok(1.0));
+    /// ```
+    pub fn scoped(slots: &mut [MaybeUninit<T>]) -> ScopedRingBuffer<'_, T> {
+        ScopedRingBuffer {
+            buffer: ManuallyDrop::new(RingBuffer::borrowed(slots.as_mut_ptr().cast(), slots.len())),
+            _slots: PhantomData,
+        }
+    }
+}
+
+impl<T> ScopedRingBuffer<'_, T> {
+    /// Returns a [`Producer`] and a [`Consumer`] that refer to this ring buffer.
+    ///
+    /// Items that are still in the queue from a previous call are kept,
+    /// but the queue is neither closed nor abandoned anymore.
+    pub fn split(&mut self) -> (BorrowedProducer<'_, T>, BorrowedConsumer<'_, T>) {
+        self.buffer.split_borrowed()
+    }
+
+    /// Returns the capacity of the queue (which is the number of borrowed slots).
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity
+    }
+}
+
+impl<T> Drop for ScopedRingBuffer<'_, T> {
+    /// Drops all items that are still in the queue.
+    fn drop(&mut self) {
+        self.buffer.drop_items();
+    }
+}
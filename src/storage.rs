@@ -0,0 +1,112 @@
+//! Custom memory for the slots of a [`RingBuffer`].
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::mem::MaybeUninit;
+
+// This is used in the documentation.
+#[allow(unused_imports)]
+use crate::RingBuffer;
+
+/// Memory for the slots of a [`RingBuffer`], see [`RingBuffer::with_storage()`].
+///
+/// By default, [`RingBuffer::new()`] allocates its slots with a `Vec<T>`.
+/// This trait can be implemented to provide the memory in another way,
+/// e.g. from an arena allocator, from huge pages or from device-visible memory.
+/// The deallocation strategy is implemented in the [`Drop`] implementation of the storage,
+/// which is called after all remaining items have been dropped.
+///
+/// It is implemented for `Box<[MaybeUninit<T>]>` and `&'static mut [MaybeUninit<T>]`.
+///
+/// # Safety
+///
+/// The pointer returned from [`Storage::as_mut_ptr()`] must be properly aligned and valid
+/// for reads and writes of [`Storage::capacity()`] consecutive values of type `T`.
+/// It must stay valid (even if the storage object itself is moved) until the storage is dropped.
+/// The memory must not be accessed by anything else in the meantime.
+/// [`Storage::capacity()`] must always return the same value.
+///
+/// # Examples
+///
+/// Slots with a larger alignment than required by the type `T`:
+///
+/// ```
+/// use std::alloc::{alloc, dealloc, Layout};
+/// use rtrb::{RingBuffer, Storage};
+///
+/// struct PageAligned {
+///     ptr: *mut f32,
+///     layout: Layout,
+/// }
+///
+/// impl PageAligned {
+///     fn new(capacity: usize) -> Self {
+///         let size = capacity * std::mem::size_of::<f32>();
+///         let layout = Layout::from_size_align(size, 4096).unwrap();
+///         let ptr = unsafe { alloc(layout) } as *mut f32;
+///         assert!(!ptr.is_null());
+///         PageAligned { ptr, layout }
+///     }
+/// }
+///
+/// // The memory is owned exclusively.
+/// unsafe impl Send for PageAligned {}
+///
+/// unsafe impl Storage<f32> for PageAligned {
+///     fn as_mut_ptr(&mut self) -> *mut f32 {
+///         self.ptr
+///     }
+///
+///     fn capacity(&self) -> usize {
+///         self.layout.size() / std::mem::size_of::<f32>()
+///     }
+/// }
+///
+/// impl Drop for PageAligned {
+///     fn drop(&mut self) {
+///         unsafe { dealloc(self.ptr as *mut u8, self.layout) };
+///     }
+/// }
+///
+/// let (mut producer, mut consumer) = RingBuffer::with_storage(PageAligned::new(1024));
+/// assert_eq!(producer.slots(), 1024);
+/// assert_eq!(producer.push(0.5), Ok(()));
+/// assert_eq!(consumer.pop(), Ok(0.5));
+/// ```
+pub unsafe trait Storage<T>: Send {
+    /// Returns a pointer to the first slot.
+    ///
+    /// This is called only once, after the storage has been moved into the [`RingBuffer`].
+    fn as_mut_ptr(&mut self) -> *mut T;
+
+    /// Returns the number of slots, which will be the capacity of the [`RingBuffer`].
+    fn capacity(&self) -> usize;
+}
+
+unsafe impl<T: Send> Storage<T> for Box<[MaybeUninit<T>]> {
+    fn as_mut_ptr(&mut self) -> *mut T {
+        <[MaybeUninit<T>]>::as_mut_ptr(self).cast()
+    }
+
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+unsafe impl<T: Send> Storage<T> for &'static mut [MaybeUninit<T>] {
+    fn as_mut_ptr(&mut self) -> *mut T {
+        <[MaybeUninit<T>]>::as_mut_ptr(self).cast()
+    }
+
+    fn capacity(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> fmt::Debug for dyn Storage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Storage")
+            .field("capacity", &self.capacity())
+            .finish_non_exhaustive()
+    }
+}
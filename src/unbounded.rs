@@ -0,0 +1,295 @@
+//! An unbounded queue made of linked ring buffer segments.
+//!
+//! An unbounded queue can be created with [`RingBuffer::new_unbounded()`],
+//! which returns a [`Producer`] and a [`Consumer`] from this module.
+//!
+//! Items are pushed into a ring buffer segment with a fixed capacity.
+//! When a segment is full, the [`Producer`] continues with a new segment
+//! and links it to the previous one, so pushing never fails
+//! (unless the [`Consumer`] has been dropped).
+//! The [`Consumer`] reads all items of a segment and then follows the link to the next one.
+//! Segments that have been drained are handed back to the [`Producer`] for reuse
+//! (a few of them are kept, the others are deallocated on the consumer's thread).
+//!
+//! Unless a recycled segment is available, pushing into a full segment allocates memory,
+//! therefore this is *not* realtime-safe on the producer side.
+//! The consumer side doesn't allocate, but it might deallocate.
+//!
+//! # Examples
+//!
+//! ```
+//! use rtrb::{PopError, RingBuffer};
+//!
+//! let (mut producer, mut consumer) = RingBuffer::new_unbounded(2);
+//! for i in 0..5 {
+//!     producer.push(i).unwrap();
+//! }
+//! for i in 0..5 {
+//!     assert_eq!(consumer.pop(), Ok(i));
+//! }
+//! assert_eq!(consumer.pop(), Err(PopError::Empty));
+//! ```
+
+use core::mem;
+
+use crate::chunks::{ChunkError, ReadChunk, WriteChunkUninit};
+use crate::{PeekError, PopError, PushError, RingBuffer};
+
+/// The number of drained segments that can wait for reuse.
+const RECYCLED_SEGMENTS: usize = 2;
+
+/// The connection from a segment to the next one.
+///
+/// Each segment has its own link queue with capacity `1`.
+#[derive(Debug)]
+struct Link<T> {
+    /// The producer of the previous segment, which is not used anymore.
+    previous: crate::Producer<T>,
+
+    /// The consumer of the next segment.
+    consumer: crate::Consumer<T>,
+
+    /// The link queue of the next segment.
+    next: crate::Consumer<Link<T>>,
+}
+
+impl<T> RingBuffer<T> {
+    /// Creates an unbounded queue made of segments with the given capacity.
+    ///
+    /// See the [`unbounded`](crate::unbounded) module for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segment_capacity` is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rtrb::RingBuffer;
+    ///
+    /// let (mut producer, mut consumer) = RingBuffer::new_unbounded(1024);
+    /// producer.push(0.5f32).unwrap();
+    /// assert_eq!(consumer.pop(), Ok(0.5));
+    /// ```
+    #[must_use]
+    pub fn new_unbounded(segment_capacity: usize) -> (Producer<T>, Consumer<T>) {
+        assert_ne!(segment_capacity, 0, "segment capacity must not be zero");
+        let (producer, consumer) = RingBuffer::new(segment_capacity);
+        let (link_producer, link_consumer) = RingBuffer::new(1);
+        let (recycle_producer, recycle_consumer) = RingBuffer::new(RECYCLED_SEGMENTS);
+        let p = Producer {
+            producer,
+            link: link_producer,
+            recycled: recycle_consumer,
+            segment_capacity,
+        };
+        let c = Consumer {
+            consumer,
+            link: link_consumer,
+            recycled: recycle_producer,
+        };
+        (p, c)
+    }
+}
+
+/// The producer side of an unbounded queue.
+///
+/// Can be created with [`RingBuffer::new_unbounded()`].
+///
+/// *See also the [module-level documentation](crate::unbounded).*
+#[derive(Debug)]
+pub struct Producer<T> {
+    /// The producer of the current segment.
+    producer: crate::Producer<T>,
+
+    /// Used to publish the next segment.
+    link: crate::Producer<Link<T>>,
+
+    /// Drained segments that have been handed back by the consumer.
+    recycled: crate::Consumer<RingBuffer<T>>,
+
+    /// The capacity of each segment.
+    segment_capacity: usize,
+}
+
+impl<T> Producer<T> {
+    /// Pushes an element into the queue, continuing with a new segment if necessary.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Consumer`] has been dropped, the element is returned back
+    /// in [`PushError::Closed`].
+    /// [`PushError::Full`] is never returned.
+    pub fn push(&mut self, value: T) -> Result<(), PushError<T>> {
+        match self.producer.push(value) {
+            Err(PushError::Full(value)) => {
+                if !self.next_segment() {
+                    return Err(PushError::Closed(value));
+                }
+                self.producer.push(value)
+            }
+            result => result,
+        }
+    }
+
+    /// Returns `n` (uninitialized) slots for writing,
+    /// continuing with a new segment if the current one doesn't have enough slots.
+    ///
+    /// The remaining slots of the current segment stay unused in this case.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is larger than the segment capacity, an error
+    /// (containing the segment capacity) is returned.
+    /// If the [`Consumer`] has been dropped, [`ChunkError::Closed`] is returned.
+    pub fn write_chunk_uninit(&mut self, n: usize) -> Result<WriteChunkUninit<'_, T>, ChunkError> {
+        if n > self.segment_capacity {
+            return Err(ChunkError::TooFewSlots(self.segment_capacity));
+        }
+        if self.producer.slots() < n && !self.next_segment() {
+            return Err(ChunkError::Closed);
+        }
+        self.producer.write_chunk_uninit(n)
+    }
+
+    /// Returns the capacity of each segment.
+    pub fn segment_capacity(&self) -> usize {
+        self.segment_capacity
+    }
+
+    /// Returns `true` if the [`Consumer`] has been dropped.
+    pub fn is_abandoned(&self) -> bool {
+        self.recycled.is_abandoned()
+    }
+
+    /// Switches to a recycled or newly allocated segment.
+    ///
+    /// Returns `false` if the consumer has been dropped.
+    fn next_segment(&mut self) -> bool {
+        if self.recycled.is_abandoned() {
+            return false;
+        }
+        let (producer, consumer) = match self.recycled.pop() {
+            Ok(buffer) => buffer.split(),
+            Err(_) => RingBuffer::new(self.segment_capacity),
+        };
+        let (link_producer, next) = RingBuffer::new(1);
+        let previous = mem::replace(&mut self.producer, producer);
+        // Each link queue is used only once, so this can only fail if the consumer is gone.
+        let _ = self.link.push(Link {
+            previous,
+            consumer,
+            next,
+        });
+        self.link = link_producer;
+        true
+    }
+}
+
+/// The consumer side of an unbounded queue.
+///
+/// Can be created with [`RingBuffer::new_unbounded()`].
+///
+/// *See also the [module-level documentation](crate::unbounded).*
+#[derive(Debug)]
+pub struct Consumer<T> {
+    /// The consumer of the current segment.
+    consumer: crate::Consumer<T>,
+
+    /// Used to receive the next segment.
+    link: crate::Consumer<Link<T>>,
+
+    /// Used to hand drained segments back to the producer.
+    recycled: crate::Producer<RingBuffer<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Attempts to pop an element, continuing with the next segment if necessary.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::pop()`].
+    pub fn pop(&mut self) -> Result<T, PopError> {
+        loop {
+            match self.consumer.pop() {
+                Err(e) if !self.follow() => return Err(e),
+                Err(_) => {}
+                result => return result,
+            }
+        }
+    }
+
+    /// Attempts to read an element without removing it.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::peek()`].
+    pub fn peek(&mut self) -> Result<&T, PeekError> {
+        self.slots();
+        self.consumer.peek()
+    }
+
+    /// Returns `n` slots for reading.
+    ///
+    /// A chunk never spans multiple segments, therefore the items of the current
+    /// segment have to be read before items from the next segment can be obtained.
+    ///
+    /// # Errors
+    ///
+    /// See [`crate::Consumer::read_chunk()`].
+    pub fn read_chunk(&mut self, n: usize) -> Result<ReadChunk<'_, T>, ChunkError> {
+        self.slots();
+        self.consumer.read_chunk(n)
+    }
+
+    /// Returns the number of slots available for reading in the current segment.
+    ///
+    /// If the current segment is empty and there is a next segment,
+    /// this continues with the next segment.
+    pub fn slots(&mut self) -> usize {
+        loop {
+            let slots = self.consumer.slots();
+            if slots != 0 || !self.follow() {
+                return slots;
+            }
+        }
+    }
+
+    /// Returns `true` if there are currently no items available for reading.
+    pub fn is_empty(&mut self) -> bool {
+        self.slots() == 0
+    }
+
+    /// Returns `true` if the [`Producer`] has been dropped.
+    ///
+    /// Items might still be available for reading.
+    pub fn is_abandoned(&self) -> bool {
+        self.recycled.is_abandoned()
+    }
+
+    /// Continues with the next segment if the current one is drained.
+    ///
+    /// Returns `true` if this has happened.
+    fn follow(&mut self) -> bool {
+        // Once the link has been published, no more items are written
+        // into the current segment.
+        if self.link.is_empty() || !self.consumer.is_empty() {
+            return false;
+        }
+        let Link {
+            previous,
+            consumer,
+            next,
+        } = match self.link.pop() {
+            Ok(link) => link,
+            Err(_) => unreachable!(),
+        };
+        let drained = mem::replace(&mut self.consumer, consumer);
+        self.link = next;
+        if let Ok(buffer) = RingBuffer::reunite(previous, drained) {
+            // If enough segments are waiting for reuse, this one is deallocated.
+            let _ = self.recycled.push(buffer);
+        }
+        true
+    }
+}
@@ -0,0 +1,128 @@
+//! A slot for a single [`Waker`] that can be shared between the two sides of a ring buffer.
+//!
+//! One side registers a [`Waker`] before it starts waiting,
+//! the other side calls [`AtomicWaker::wake()`] after it has made progress.
+//!
+//! The locking protocol is similar to the one used by `futures::task::AtomicWaker`,
+//! but the registered [`Waker`] is only ever woken by reference,
+//! so the waking side doesn't clone or drop it while the ring buffer is in use.
+//! However, [`Waker::wake_by_ref()`] calls into the executor (or unparks a thread),
+//! which is not necessarily realtime-safe.
+//! Also, the stored [`Waker`] is dropped together with the ring buffer,
+//! which happens on the thread that drops the last handle.
+//!
+//! As long as no [`Waker`] is registered, [`AtomicWaker::wake()`] doesn't write to shared memory.
+
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::sync::atomic::{fence, AtomicUsize, Ordering};
+use core::task::Waker;
+
+/// Neither side is accessing the slot.
+const IDLE: usize = 0;
+
+/// The waiting side is currently updating the slot.
+const REGISTERING: usize = 0b01;
+
+/// The waking side is currently accessing the slot.
+const WAKING: usize = 0b10;
+
+/// The registered [`Waker`] is still waiting to be woken.
+const ARMED: usize = 0b100;
+
+pub(crate) struct AtomicWaker {
+    state: AtomicUsize,
+    slot: UnsafeCell<Option<Waker>>,
+}
+
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+    pub(crate) const fn new() -> Self {
+        AtomicWaker {
+            state: AtomicUsize::new(IDLE),
+            slot: UnsafeCell::new(None),
+        }
+    }
+
+    /// Registers `waker` to be woken on the next call to [`AtomicWaker::wake()`].
+    ///
+    /// Must only ever be called from one side of the ring buffer.
+    ///
+    /// After registering, the caller must check again whether it still has to wait.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut state = self.state.load(Ordering::Acquire);
+        loop {
+            if state & WAKING != 0 {
+                // wake() is running concurrently, we don't have to wait.
+                waker.wake_by_ref();
+                return;
+            }
+            debug_assert_eq!(state & REGISTERING, 0);
+            match self.state.compare_exchange_weak(
+                state,
+                state | REGISTERING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => break,
+                Err(actual) => state = actual,
+            }
+        }
+        // Safety: The REGISTERING bit gives exclusive access to the slot.
+        let slot = unsafe { &mut *self.slot.get() };
+        match slot {
+            Some(old) if old.will_wake(waker) => {}
+            _ => *slot = Some(waker.clone()),
+        }
+        if self
+            .state
+            .compare_exchange(
+                state | REGISTERING,
+                ARMED,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .is_err()
+        {
+            // wake() has been called concurrently (the WAKING bit is set),
+            // but it couldn't access the slot, so we have to do it here.
+            self.state.swap(IDLE, Ordering::AcqRel);
+            waker.wake_by_ref();
+        }
+        // Pairs with the fence in wake(): either the caller's subsequent check
+        // sees the other side's progress, or wake() sees the ARMED bit.
+        fence(Ordering::SeqCst);
+    }
+
+    /// Wakes the registered [`Waker`], if there is one that hasn't been woken yet.
+    ///
+    /// This is only called if blocking has been enabled for the waiting side,
+    /// see `RingBuffer::wake_producer()` and `RingBuffer::wake_consumer()`.
+    pub(crate) fn wake(&self) {
+        // Pairs with the fence in register(), see above.
+        fence(Ordering::SeqCst);
+        if self.state.load(Ordering::Relaxed) == IDLE {
+            // Nobody is waiting, there is no need to take the lock.
+            return;
+        }
+        let state = self.state.fetch_or(WAKING, Ordering::AcqRel);
+        if state & (REGISTERING | WAKING) == 0 {
+            if state & ARMED != 0 {
+                // Safety: The WAKING bit gives exclusive access to the slot.
+                if let Some(waker) = unsafe { &*self.slot.get() } {
+                    waker.wake_by_ref();
+                }
+            }
+            self.state.fetch_and(!(WAKING | ARMED), Ordering::Release);
+        }
+    }
+}
+
+impl fmt::Debug for AtomicWaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicWaker").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,139 @@
+#![cfg(feature = "std")]
+
+use std::time::{Duration, Instant};
+
+use rtrb::chunks::{ChunkError, ChunkTimeoutError};
+use rtrb::{Blocking, PopError, PopTimeoutError, PushError, PushTimeoutError, RingBuffer};
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 10_000;
+    let (mut p, mut c) = RingBuffer::with_blocking(3, Blocking::Both);
+    let pop_thread = std::thread::spawn(move || {
+        for i in 0..COUNT {
+            assert_eq!(c.pop_blocking(), Ok(i));
+        }
+        assert_eq!(c.pop_blocking(), Err(PopError::Closed));
+    });
+    let push_thread = std::thread::spawn(move || {
+        for i in 0..COUNT {
+            p.push_blocking(i).unwrap();
+        }
+    });
+    push_thread.join().unwrap();
+    pop_thread.join().unwrap();
+}
+
+#[test]
+fn parallel_chunks() {
+    const COUNT: usize = 1_000;
+    let (mut p, mut c) = RingBuffer::with_blocking(5, Blocking::Both);
+    let pop_thread = std::thread::spawn(move || {
+        for i in 0..COUNT {
+            let chunk = c.read_chunk_blocking(3).unwrap();
+            assert_eq!(chunk.into_iter().collect::<Vec<_>>(), [i, i + 1, i + 2]);
+        }
+        assert_eq!(
+            c.read_chunk_blocking(1).unwrap_err(),
+            ChunkError::TooFewSlots(0)
+        );
+    });
+    let push_thread = std::thread::spawn(move || {
+        for i in 0..COUNT {
+            let chunk = p.write_chunk_uninit_blocking(3).unwrap();
+            assert_eq!(chunk.fill_from_iter(i..), 3);
+        }
+    });
+    push_thread.join().unwrap();
+    pop_thread.join().unwrap();
+}
+
+#[test]
+fn abandoned() {
+    let (mut p, c) = RingBuffer::with_blocking(1, Blocking::Producer);
+    assert_eq!(p.push_blocking(10), Ok(()));
+    let push_thread = std::thread::spawn(move || p.push_blocking(20));
+    drop(c);
+    assert_eq!(push_thread.join().unwrap(), Err(PushError::Closed(20)));
+}
+
+#[test]
+fn too_large_chunk() {
+    let (mut p, mut c) = RingBuffer::<i32>::with_blocking(2, Blocking::Both);
+    assert_eq!(
+        p.write_chunk_blocking(3).unwrap_err(),
+        ChunkError::TooFewSlots(2)
+    );
+    assert_eq!(
+        c.read_chunk_blocking(3).unwrap_err(),
+        ChunkError::TooFewSlots(0)
+    );
+}
+
+#[test]
+#[should_panic(expected = "blocking is not enabled for the consumer")]
+fn not_enabled() {
+    let (_p, mut c) = RingBuffer::<i32>::with_blocking(1, Blocking::Producer);
+    let _ = c.pop_blocking();
+}
+
+#[test]
+fn timeout() {
+    let timeout = Duration::from_millis(10);
+    let (mut p, mut c) = RingBuffer::with_blocking(2, Blocking::Both);
+    assert_eq!(c.pop_timeout(timeout), Err(PopTimeoutError::Timeout));
+    assert_eq!(
+        c.read_chunk_timeout(1, timeout).unwrap_err(),
+        ChunkTimeoutError::Timeout(0)
+    );
+    assert_eq!(p.push_timeout(10, timeout), Ok(()));
+    assert_eq!(
+        p.write_chunk_timeout(2, timeout).unwrap_err(),
+        ChunkTimeoutError::Timeout(1)
+    );
+    assert_eq!(p.push_deadline(20, Instant::now() + timeout), Ok(()));
+    assert_eq!(
+        p.push_deadline(30, Instant::now() + timeout),
+        Err(PushTimeoutError::Timeout(30))
+    );
+    assert_eq!(
+        p.write_chunk_uninit_deadline(1, Instant::now() + timeout)
+            .unwrap_err(),
+        ChunkTimeoutError::Timeout(0)
+    );
+    assert_eq!(c.pop_deadline(Instant::now() + timeout), Ok(10));
+    assert_eq!(
+        c.read_chunk_deadline(3, Instant::now() + timeout)
+            .unwrap_err(),
+        ChunkTimeoutError::TooFewSlots(1)
+    );
+}
+
+#[test]
+fn timeout_parallel() {
+    let (mut p, mut c) = RingBuffer::with_blocking(1, Blocking::Consumer);
+    let pop_thread = std::thread::spawn(move || {
+        let timeout = Duration::from_secs(60);
+        assert_eq!(c.pop_timeout(timeout), Ok(10));
+        assert_eq!(c.pop_timeout(timeout), Err(PopTimeoutError::Closed));
+    });
+    std::thread::sleep(Duration::from_millis(10));
+    assert_eq!(p.push(10), Ok(()));
+    drop(p);
+    pop_thread.join().unwrap();
+}
+
+#[test]
+fn timeout_abandoned() {
+    let (mut p, c) = RingBuffer::with_blocking(1, Blocking::Producer);
+    assert_eq!(p.push(10), Ok(()));
+    drop(c);
+    assert_eq!(
+        p.push_timeout(20, Duration::MAX),
+        Err(PushTimeoutError::Closed(20))
+    );
+    assert_eq!(
+        p.write_chunk_uninit_timeout(1, Duration::MAX).unwrap_err(),
+        ChunkTimeoutError::Closed
+    );
+}
@@ -0,0 +1,224 @@
+#![cfg(feature = "alloc")]
+
+use rtrb::{CreateError, RingBuffer};
+
+#[test]
+fn prefault() {
+    let (mut p, mut c) = RingBuffer::builder(10_000).prefault(true).build().unwrap();
+    assert_eq!(p.buffer().capacity(), 10_000);
+    for i in 0..10_000u64 {
+        p.push(i).unwrap();
+    }
+    for i in 0..10_000 {
+        assert_eq!(c.pop(), Ok(i));
+    }
+    let (p, _c) = RingBuffer::<()>::builder(5).prefault(true).build().unwrap();
+    assert_eq!(p.buffer().capacity(), 5);
+}
+
+/// Returns `true` if all pages containing the memory from `ptr` to `ptr + len` are resident.
+#[cfg(target_os = "linux")]
+fn resident(ptr: *const u8, len: usize) -> bool {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = ptr as usize / page_size * page_size;
+    let end = ptr as usize + len;
+    let mut pages = vec![0u8; (end - start + page_size - 1) / page_size];
+    let result = unsafe { libc::mincore(start as *mut _, end - start, pages.as_mut_ptr()) };
+    assert_eq!(result, 0);
+    pages.iter().all(|&page| page & 1 != 0)
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn prefault_all_pages() {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    // Large enough to be freshly mapped, the slots typically don't start on a page boundary.
+    let capacity = 64 * page_size - 8;
+    let (mut p, _c) = RingBuffer::<u8>::builder(capacity)
+        .prefault(true)
+        .build()
+        .unwrap();
+    let ptr = p.write_chunk_uninit(1).unwrap().as_mut_slices().0.as_ptr();
+    assert!(resident(ptr.cast(), capacity));
+}
+
+#[test]
+fn capacity_overflow() {
+    assert_eq!(
+        RingBuffer::<u8>::builder(usize::MAX).build().unwrap_err(),
+        CreateError::CapacityOverflow
+    );
+    assert!(RingBuffer::<()>::builder(usize::MAX / 2).build().is_ok());
+    assert_eq!(
+        RingBuffer::<()>::builder(usize::MAX / 2)
+            .overwriting(true)
+            .build()
+            .unwrap_err(),
+        CreateError::CapacityOverflow
+    );
+}
+
+#[cfg(all(feature = "mlock", target_os = "linux"))]
+#[test]
+fn lock_memory() {
+    let (mut p, mut c) = RingBuffer::builder(1000)
+        .lock_memory(true)
+        .prefault(true)
+        .build()
+        .unwrap();
+    for i in 0..2000 {
+        p.push(i.to_string()).unwrap();
+        assert_eq!(c.pop(), Ok(i.to_string()));
+    }
+    p.push(String::from("dropped with the ring buffer"))
+        .unwrap();
+
+    let (p, c) = RingBuffer::<()>::builder(5)
+        .lock_memory(true)
+        .build()
+        .unwrap();
+    assert_eq!(p.buffer().capacity(), 5);
+    assert_eq!(c.buffer().capacity(), 5);
+
+    assert_eq!(
+        RingBuffer::<u64>::builder(usize::MAX / 2)
+            .lock_memory(true)
+            .build()
+            .unwrap_err(),
+        CreateError::CapacityOverflow
+    );
+}
+
+/// Returns the amount of locked memory (in kB) in the memory mapping containing `ptr`.
+#[cfg(all(feature = "mlock", target_os = "linux"))]
+fn locked_kb(ptr: *const u8) -> usize {
+    let smaps = std::fs::read_to_string("/proc/self/smaps").unwrap();
+    let mut inside = false;
+    for line in smaps.lines() {
+        let range = line.split(' ').next().unwrap();
+        if let Some((start, end)) = range.split_once('-') {
+            if let (Ok(start), Ok(end)) = (
+                usize::from_str_radix(start, 16),
+                usize::from_str_radix(end, 16),
+            ) {
+                inside = (start..end).contains(&(ptr as usize));
+                continue;
+            }
+        }
+        if let Some(locked) = line.strip_prefix("Locked:") {
+            if inside {
+                return locked.trim().trim_end_matches(" kB").parse().unwrap();
+            }
+        }
+    }
+    panic!("memory mapping not found");
+}
+
+#[cfg(all(feature = "mlock", target_os = "linux"))]
+#[test]
+fn lock_memory_separate_pages() {
+    let mut buffers: Vec<_> = (0..8)
+        .map(|_| {
+            RingBuffer::<u8>::builder(16)
+                .lock_memory(true)
+                .build()
+                .unwrap()
+        })
+        .collect();
+    let (mut p, c) = buffers.pop().unwrap();
+    let ptr = p.write_chunk_uninit(1).unwrap().as_mut_slices().0.as_ptr();
+    assert!(locked_kb(ptr.cast()) > 0);
+    // Unlocking the memory of the other buffers must not unlock this one.
+    drop(buffers);
+    assert!(locked_kb(ptr.cast()) > 0);
+    drop((p, c));
+}
+
+#[test]
+fn memory_lock_error() {
+    let err = CreateError::MemoryLock(12);
+    assert_eq!(
+        err.to_string(),
+        "locking ring buffer memory failed (OS error 12)"
+    );
+}
+
+#[test]
+fn options() {
+    let (mut p, mut c) = RingBuffer::builder(2).overwriting(true).build().unwrap();
+    assert_eq!(p.force_push(1), None);
+    assert_eq!(p.force_push(2), None);
+    assert_eq!(p.force_push(3), Some(1));
+    assert_eq!(c.pop(), Ok(2));
+
+    let storage: Box<[std::mem::MaybeUninit<i32>]> =
+        (0..3).map(|_| std::mem::MaybeUninit::uninit()).collect();
+    let (mut p, mut c) = RingBuffer::builder(3)
+        .storage(storage)
+        .prefault(true)
+        .build()
+        .unwrap();
+    assert_eq!(p.slots(), 3);
+    p.push(10).unwrap();
+    assert_eq!(c.pop(), Ok(10));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn blocking() {
+    use rtrb::Blocking;
+    use std::time::Duration;
+
+    let (mut p, mut c) = RingBuffer::builder(1)
+        .blocking(Blocking::Both)
+        .build()
+        .unwrap();
+    p.push(1).unwrap();
+    assert!(p.push_timeout(2, Duration::from_millis(1)).is_err());
+    assert_eq!(c.pop_timeout(Duration::from_millis(1)), Ok(1));
+}
+
+#[test]
+fn invalid_configuration() {
+    use rtrb::{Blocking, BuilderOption};
+
+    let err = RingBuffer::<u8>::builder(2)
+        .overwriting(true)
+        .blocking(Blocking::Producer)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        CreateError::IncompatibleOptions(BuilderOption::Overwriting, BuilderOption::Blocking)
+    );
+    assert_eq!(
+        err.to_string(),
+        "ring buffer options overwriting and blocking are incompatible"
+    );
+    RingBuffer::<u8>::builder(2)
+        .overwriting(true)
+        .blocking(Blocking::Consumer)
+        .build()
+        .unwrap();
+
+    let storage: Box<[std::mem::MaybeUninit<u8>]> =
+        (0..3).map(|_| std::mem::MaybeUninit::uninit()).collect();
+    assert_eq!(
+        RingBuffer::builder(4).storage(storage).build().unwrap_err(),
+        CreateError::StorageCapacity(3)
+    );
+
+    #[cfg(all(feature = "mlock", target_os = "linux"))]
+    {
+        let storage: Box<[std::mem::MaybeUninit<u8>]> =
+            (0..3).map(|_| std::mem::MaybeUninit::uninit()).collect();
+        assert_eq!(
+            RingBuffer::builder(3)
+                .storage(storage)
+                .lock_memory(true)
+                .build()
+                .unwrap_err(),
+            CreateError::IncompatibleOptions(BuilderOption::Storage, BuilderOption::LockMemory)
+        );
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "alloc")]
+
+use std::collections::VecDeque;
+use std::mem::MaybeUninit;
+use std::rc::Rc;
+
+use rtrb::{PopError, ReuniteError, RingBuffer};
+
+#[test]
+fn into_vec() {
+    let (mut p, mut c) = RingBuffer::new(4);
+    for i in 0..6 {
+        p.push(i).unwrap();
+        if i < 3 {
+            c.pop().unwrap();
+        }
+    }
+    let buffer = RingBuffer::reunite(p, c).unwrap();
+    assert_eq!(buffer.capacity(), 4);
+    let v = Vec::from(buffer);
+    assert_eq!(v, [3, 4, 5]);
+    assert_eq!(v.capacity(), 4);
+}
+
+#[test]
+fn into_vec_deque() {
+    let (mut p, c) = RingBuffer::new(3);
+    p.push(String::from("a")).unwrap();
+    p.push(String::from("b")).unwrap();
+    let buffer = RingBuffer::reunite(p, c).unwrap();
+    let d = VecDeque::from(buffer);
+    assert_eq!(d, ["a", "b"]);
+}
+
+#[test]
+fn custom_storage() {
+    let storage: Box<[MaybeUninit<String>]> = (0..3).map(|_| MaybeUninit::uninit()).collect();
+    let (mut p, mut c) = RingBuffer::with_storage(storage);
+    for s in ["a", "b", "c", "d"] {
+        if p.push(s.to_string()).is_err() {
+            c.pop().unwrap();
+            p.push(s.to_string()).unwrap();
+        }
+    }
+    let buffer = RingBuffer::reunite(p, c).unwrap();
+    assert_eq!(Vec::from(buffer), ["b", "c", "d"]);
+}
+
+#[test]
+fn split_again() {
+    let (mut p, mut c) = RingBuffer::new(2);
+    p.push(1).unwrap();
+    c.close();
+    let (mut p, mut c) = RingBuffer::reunite(p, c).unwrap().split();
+    assert_eq!(p.push(2), Ok(()));
+    assert_eq!(c.pop(), Ok(1));
+    assert_eq!(c.pop(), Ok(2));
+    drop(p);
+    assert_eq!(c.pop(), Err(PopError::Closed));
+}
+
+#[test]
+fn different_buffers() {
+    let (p1, c1) = RingBuffer::<i32>::new(1);
+    let (p2, c2) = RingBuffer::<i32>::new(1);
+    let err = RingBuffer::reunite(p1, c2).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "producer and consumer belong to different ring buffers"
+    );
+    let ReuniteError(p1, c2) = err;
+    assert!(RingBuffer::reunite(p1, c1).is_ok());
+    assert!(RingBuffer::reunite(p2, c2).is_ok());
+}
+
+#[test]
+fn drops() {
+    let item = Rc::new(());
+    let (mut p, c) = RingBuffer::new(2);
+    p.push(item.clone()).unwrap();
+    p.push(item.clone()).unwrap();
+    let buffer = RingBuffer::reunite(p, c).unwrap();
+    assert_eq!(Rc::strong_count(&item), 3);
+    let (_, mut c) = buffer.split();
+    assert!(c.pop().is_ok());
+    assert_eq!(Rc::strong_count(&item), 2);
+    let buffer = RingBuffer::reunite(RingBuffer::new(0).0, c);
+    // Remaining items are dropped with the ring buffer.
+    drop(buffer);
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+fn from_vec() {
+    let mut items = Vec::with_capacity(3);
+    items.push(String::from("a"));
+    let (mut p, mut c) = RingBuffer::from(items).split();
+    assert_eq!(p.buffer().capacity(), 3);
+    assert_eq!(p.slots(), 2);
+    for s in ["b", "c", "d", "e"] {
+        if p.push(s.to_string()).is_err() {
+            c.pop().unwrap();
+            p.push(s.to_string()).unwrap();
+        }
+    }
+    assert_eq!(
+        Vec::from(RingBuffer::reunite(p, c).unwrap()),
+        ["c", "d", "e"]
+    );
+}
+
+#[test]
+fn from_iter_with_capacity() {
+    let (mut p, mut c) = RingBuffer::from_iter_with_capacity(3, 0..3);
+    assert!(p.is_full());
+    assert_eq!(c.slots(), 3);
+    let chunk = c.read_chunk(3).unwrap();
+    assert_eq!(chunk.as_slices(), (&[0, 1, 2][..], &[][..]));
+    chunk.commit(2);
+    assert_eq!(p.push(3), Ok(()));
+    assert_eq!(c.pop(), Ok(2));
+    assert_eq!(c.pop(), Ok(3));
+
+    let (p, c) = RingBuffer::<i32>::from_iter_with_capacity(0, None);
+    assert_eq!(p.buffer().capacity(), 0);
+    assert!(c.is_empty());
+}
+
+#[test]
+fn zero_sized_type() {
+    let (p, mut c) = RingBuffer::from(vec![(), ()]).split();
+    assert_eq!(p.buffer().capacity(), 2);
+    assert!(p.is_full());
+    assert_eq!(c.pop(), Ok(()));
+    let (p, _c) = RingBuffer::from_iter_with_capacity(5, [(); 2]);
+    assert_eq!(p.slots(), 3);
+}
+
+#[test]
+#[should_panic(expected = "too many items for the given capacity")]
+fn too_many_items() {
+    let _ = RingBuffer::from_iter_with_capacity(2, 0..3);
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "std")]
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+use rtrb::{chunks::ChunkError, Blocking, PopError, PushError, RingBuffer};
+
+struct ThreadWaker(std::thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CountingWaker(AtomicUsize);
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 10_000;
+    let (mut p, mut c) = RingBuffer::with_blocking(3, Blocking::Both);
+    let pop_thread = std::thread::spawn(move || {
+        block_on(async {
+            for i in 0..COUNT {
+                assert_eq!(c.pop_async().await, Ok(i));
+            }
+            assert_eq!(c.pop_async().await, Err(PopError::Closed));
+        })
+    });
+    let push_thread = std::thread::spawn(move || {
+        block_on(async {
+            for i in 0..COUNT {
+                p.push_async(i).await.unwrap();
+            }
+        })
+    });
+    push_thread.join().unwrap();
+    pop_thread.join().unwrap();
+}
+
+#[test]
+fn parallel_chunks() {
+    const COUNT: usize = 1_000;
+    let (mut p, mut c) = RingBuffer::with_blocking(5, Blocking::Both);
+    let pop_thread = std::thread::spawn(move || {
+        block_on(async {
+            for i in 0..COUNT {
+                let chunk = c.read_chunk_async(3).await.unwrap();
+                assert_eq!(chunk.into_iter().collect::<Vec<_>>(), [i, i + 1, i + 2]);
+            }
+            assert_eq!(
+                c.read_chunk_async(1).await.unwrap_err(),
+                ChunkError::TooFewSlots(0)
+            );
+        })
+    });
+    let push_thread = std::thread::spawn(move || {
+        block_on(async {
+            for i in 0..COUNT {
+                let chunk = p.write_chunk_uninit_async(3).await.unwrap();
+                assert_eq!(chunk.fill_from_iter(i..), 3);
+            }
+        })
+    });
+    push_thread.join().unwrap();
+    pop_thread.join().unwrap();
+}
+
+#[test]
+fn wake_on_commit() {
+    let (mut p, mut c) = RingBuffer::with_blocking(1, Blocking::Consumer);
+    let counter = Arc::new(CountingWaker::default());
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let mut future = c.pop_async();
+    assert!(Pin::new(&mut future).poll(&mut cx).is_pending());
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+    assert_eq!(p.push(10), Ok(()));
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(Pin::new(&mut future).poll(&mut cx), Poll::Ready(Ok(10)));
+}
+
+#[test]
+fn abandoned() {
+    let (mut p, c) = RingBuffer::with_blocking(1, Blocking::Producer);
+    assert_eq!(block_on(p.push_async(10)), Ok(()));
+    drop(c);
+    assert_eq!(block_on(p.push_async(20)), Err(PushError::Closed(20)));
+    assert_eq!(
+        block_on(p.write_chunk_async(1)).unwrap_err(),
+        ChunkError::Closed
+    );
+}
+
+#[test]
+#[should_panic(expected = "blocking is not enabled for the producer")]
+fn not_enabled() {
+    let (mut p, _c) = RingBuffer::<i32>::with_blocking(1, Blocking::Consumer);
+    drop(p.push_async(10));
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "futures")]
+
+use futures::executor::block_on;
+use futures::{SinkExt, StreamExt};
+
+use rtrb::{Blocking, PushError, RingBuffer};
+
+#[test]
+fn stream() {
+    let (mut p, c) = RingBuffer::with_blocking(2, Blocking::Consumer);
+    let pop_thread = std::thread::spawn(move || block_on(c.collect::<Vec<_>>()));
+    for i in 0..100 {
+        while p.push(i).is_err() {}
+    }
+    drop(p);
+    assert_eq!(pop_thread.join().unwrap(), (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn forward() {
+    let (p1, c1) = RingBuffer::with_blocking(3, Blocking::Both);
+    let (p2, c2) = RingBuffer::with_blocking(2, Blocking::Both);
+    let forward_thread = std::thread::spawn(move || block_on(c1.map(Ok).forward(p2)));
+    let push_thread = std::thread::spawn(move || {
+        let mut p1 = p1;
+        block_on(p1.send_all(&mut futures::stream::iter(0..100).map(Ok)))
+    });
+    let pop_thread = std::thread::spawn(move || block_on(c2.collect::<Vec<_>>()));
+    assert_eq!(push_thread.join().unwrap(), Ok(()));
+    assert_eq!(forward_thread.join().unwrap(), Ok(()));
+    assert_eq!(pop_thread.join().unwrap(), (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn sink_abandoned() {
+    let (mut p, c) = RingBuffer::with_blocking(1, Blocking::Producer);
+    assert_eq!(block_on(p.send(10)), Ok(()));
+    drop(c);
+    assert_eq!(block_on(p.send(20)), Err(PushError::Closed(20)));
+}
+
+#[test]
+#[should_panic(expected = "blocking is not enabled for the consumer")]
+fn stream_not_enabled() {
+    let (_p, mut c) = RingBuffer::<i32>::with_blocking(1, Blocking::Producer);
+    let _ = block_on(c.next());
+}
+
+#[test]
+#[should_panic(expected = "blocking is not enabled for the producer")]
+fn sink_not_enabled() {
+    let (mut p, _c) = RingBuffer::new(1);
+    let _ = block_on(p.send(10));
+}
@@ -0,0 +1,63 @@
+use rtrb::latest::TripleBuffer;
+
+#[test]
+fn read_and_write() {
+    let (mut w, mut r) = TripleBuffer::new(0);
+    assert_eq!(*r.read(), 0);
+    for i in 1..10 {
+        w.write(i);
+        assert!(r.has_update());
+        assert_eq!(*r.read(), i);
+        assert!(!r.has_update());
+        assert_eq!(*r.read(), i);
+    }
+    w.write(10);
+    w.write(11);
+    w.write(12);
+    assert_eq!(*r.read(), 12);
+}
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 100_000;
+    let (mut w, mut r) = TripleBuffer::new(vec![0; 3]);
+    let read_thread = std::thread::spawn(move || {
+        let mut last = 0;
+        while last < COUNT {
+            let value = r.read();
+            // Values must never be torn or go back in time.
+            assert!(value.iter().all(|&x| x == value[0]));
+            assert!(value[0] >= last);
+            last = value[0];
+        }
+    });
+    for i in 1..=COUNT {
+        w.write(vec![i; 3]);
+    }
+    read_thread.join().unwrap();
+}
+
+#[test]
+fn drops() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Clone)]
+    struct Thing;
+
+    impl Drop for Thing {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let (mut w, r) = TripleBuffer::new(Thing);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 0);
+    w.write(Thing);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+    drop(w);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+    drop(r);
+    assert_eq!(DROPPED.load(Ordering::Relaxed), 4);
+}
@@ -2,7 +2,7 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use rand::{thread_rng, Rng};
 
-use rtrb::RingBuffer;
+use rtrb::{CreateError, PopError, PushError, RingBuffer};
 
 #[test]
 fn capacity() {
@@ -13,6 +13,22 @@ fn capacity() {
     }
 }
 
+#[test]
+fn try_new() {
+    let (p, c) = RingBuffer::<i32>::try_new(5).unwrap();
+    assert_eq!(p.buffer().capacity(), 5);
+    assert_eq!(c.buffer().capacity(), 5);
+    assert_eq!(
+        RingBuffer::<()>::try_new(usize::MAX / 2 + 1).unwrap_err(),
+        CreateError::CapacityOverflow
+    );
+    assert!(RingBuffer::<()>::try_new(usize::MAX / 2).is_ok());
+    assert_eq!(
+        RingBuffer::<u64>::try_new(usize::MAX / 2).unwrap_err(),
+        CreateError::AllocError
+    );
+}
+
 #[test]
 fn zero_capacity() {
     let (mut p, mut c) = RingBuffer::<i32>::new(0);
@@ -97,6 +113,7 @@ fn drops() {
             for _ in 0..steps {
                 while c.pop().is_err() {}
             }
+            c
         });
         let push_thread = std::thread::spawn(move || {
             for _ in 0..steps {
@@ -107,11 +124,12 @@ fn drops() {
             p
         });
         p = push_thread.join().unwrap();
-        pop_thread.join().unwrap();
+        let c = pop_thread.join().unwrap();
 
         for _ in 0..additional {
             p.push(DropCounter).unwrap();
         }
+        drop(c);
 
         assert_eq!(DROPS.load(Ordering::SeqCst), steps);
         drop(p);
@@ -129,6 +147,9 @@ fn trait_impls() {
 
     assert_eq!(format!("{:?}", p.push(42).unwrap_err()), "Full(_)");
     assert_eq!(p.push(42).unwrap_err().to_string(), "full ring buffer");
+    assert_eq!(format!("{:?}", PushError::Closed(42)), "Closed(_)");
+    assert_eq!(PushError::Closed(42).to_string(), "closed ring buffer");
+    assert_eq!(PopError::Closed.to_string(), "empty and closed ring buffer");
     assert_eq!(format!("{:?}", c.pop().unwrap_err()), "Empty");
     assert_eq!(c.pop().unwrap_err().to_string(), "empty ring buffer");
     assert_eq!(format!("{:?}", c.peek().unwrap_err()), "Empty");
@@ -138,3 +159,74 @@ fn trait_impls() {
     assert_ne!(p, another_p);
     assert_ne!(c, another_c);
 }
+
+#[test]
+fn close() {
+    let (mut p, mut c) = RingBuffer::new(2);
+    assert!(!p.is_closed());
+    assert!(!c.is_closed());
+    assert_eq!(p.push(10), Ok(()));
+    p.close();
+    assert!(p.is_closed());
+    assert!(c.is_closed());
+    assert!(!p.is_abandoned());
+    assert!(!c.is_abandoned());
+    assert_eq!(p.push(20), Err(PushError::Closed(20)));
+    assert_eq!(c.pop(), Ok(10));
+    assert_eq!(c.pop(), Err(PopError::Closed));
+
+    let (mut p, mut c) = RingBuffer::new(2);
+    assert_eq!(p.push(10), Ok(()));
+    c.close();
+    assert_eq!(p.push(20), Err(PushError::Closed(20)));
+    assert_eq!(c.pop(), Ok(10));
+    assert_eq!(c.pop(), Err(PopError::Closed));
+}
+
+#[test]
+fn close_chunks() {
+    use rtrb::chunks::ChunkError;
+
+    let (mut p, mut c) = RingBuffer::new(4);
+    p.write_chunk(2).unwrap().commit_all();
+    p.close();
+    assert_eq!(p.write_chunk(1).unwrap_err(), ChunkError::Closed);
+    assert_eq!(p.write_chunk_uninit(1).unwrap_err(), ChunkError::Closed);
+    assert_eq!(ChunkError::Closed.to_string(), "closed ring buffer");
+    // Items written before closing can still be read.
+    assert_eq!(
+        c.read_chunk(2).unwrap().into_iter().collect::<Vec<i32>>(),
+        [0, 0]
+    );
+    assert_eq!(c.read_chunk(1).unwrap_err(), ChunkError::TooFewSlots(0));
+
+    let (mut p, c) = RingBuffer::<i32>::new(4);
+    drop(c);
+    assert_eq!(p.write_chunk_uninit(0).unwrap_err(), ChunkError::Closed);
+}
+
+#[test]
+fn close_parallel() {
+    const COUNT: usize = 1_000;
+    let (mut p, mut c) = RingBuffer::new(3);
+    let pop_thread = std::thread::spawn(move || {
+        let mut i = 0;
+        loop {
+            match c.pop() {
+                Ok(x) => {
+                    assert_eq!(x, i);
+                    i += 1;
+                }
+                Err(PopError::Empty) => {}
+                Err(PopError::Closed) => break,
+            }
+        }
+        // No items must be lost when closing:
+        assert_eq!(i, COUNT);
+    });
+    for i in 0..COUNT {
+        while p.push(i).is_err() {}
+    }
+    p.close();
+    pop_thread.join().unwrap();
+}
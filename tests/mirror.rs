@@ -0,0 +1,82 @@
+#![cfg(all(feature = "mirror", target_os = "linux"))]
+
+use std::io::{ErrorKind, Read, Write};
+
+use rtrb::RingBuffer;
+
+#[test]
+fn capacity() {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let (p, _c) = RingBuffer::<u8>::new_mirrored(1).unwrap();
+    assert_eq!(p.buffer().capacity(), page_size);
+    let (p, _c) = RingBuffer::<u32>::new_mirrored(page_size / 4 + 1).unwrap();
+    assert_eq!(p.buffer().capacity(), page_size / 2);
+    let (p, _c) = RingBuffer::<[u8; 3]>::new_mirrored(1).unwrap();
+    assert_eq!(p.buffer().capacity(), page_size);
+}
+
+#[test]
+fn zero_size() {
+    let err = RingBuffer::<u8>::new_mirrored(0).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = RingBuffer::<()>::new_mirrored(10).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    let err = RingBuffer::<u64>::new_mirrored(usize::MAX).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn contiguous_chunks() {
+    let (mut p, mut c) = RingBuffer::new_mirrored(1).unwrap();
+    let capacity = p.buffer().capacity();
+    let n = capacity / 3 * 2;
+    for i in 0..10u8 {
+        let mut chunk = p.write_chunk(n).unwrap();
+        assert!(chunk.as_mut_slices().1.is_empty());
+        chunk.as_mut_slice().unwrap().fill(i);
+        chunk.commit_all();
+        let chunk = c.read_chunk(n).unwrap();
+        assert!(chunk.as_slices().1.is_empty());
+        assert!(chunk.as_slice().unwrap().iter().all(|&x| x == i));
+        chunk.commit_all();
+    }
+}
+
+#[test]
+fn read_and_write() {
+    let (mut p, mut c) = RingBuffer::new_mirrored(1).unwrap();
+    let capacity = p.buffer().capacity();
+    let data: Vec<u8> = (0..capacity).map(|i| i as u8).collect();
+    let mut buf = vec![0; capacity];
+    for _ in 0..5 {
+        assert_eq!(p.write(&data[..capacity - 1]).unwrap(), capacity - 1);
+        assert_eq!(c.read(&mut buf).unwrap(), capacity - 1);
+        assert_eq!(buf[..capacity - 1], data[..capacity - 1]);
+    }
+}
+
+#[test]
+fn drops() {
+    let (mut p, mut c) = RingBuffer::new_mirrored(1).unwrap();
+    let capacity = p.buffer().capacity();
+    for i in 0..capacity + 5 {
+        p.push(i.to_string()).unwrap();
+        if i >= capacity / 2 {
+            assert_eq!(c.pop(), Ok((i - capacity / 2).to_string()));
+        }
+    }
+    // This chunk wraps around, the remaining items are dropped with the ring buffer.
+    let chunk = c.read_chunk(capacity / 2).unwrap();
+    let slice = chunk.as_slice().unwrap();
+    assert_eq!(slice[0], (capacity / 2 + 5).to_string());
+    assert_eq!(slice[capacity / 2 - 1], (capacity + 4).to_string());
+}
+
+#[test]
+fn not_mirrored() {
+    let (mut p, mut c) = RingBuffer::new(4);
+    assert!(p.write_chunk(1).unwrap().as_mut_slice().is_none());
+    assert!(p.write_chunk_uninit(1).unwrap().as_mut_slice().is_none());
+    p.push(1).unwrap();
+    assert!(c.read_chunk(1).unwrap().as_slice().is_none());
+}
@@ -0,0 +1,188 @@
+use rtrb::chunks::ChunkError;
+use rtrb::{PopError, RingBuffer};
+
+#[test]
+fn force_push() {
+    let (mut p, mut c) = RingBuffer::new_overwriting(2);
+    assert_eq!(p.force_push(1), None);
+    assert_eq!(p.force_push(2), None);
+    assert_eq!(p.force_push(3), Some(1));
+    assert_eq!(p.force_push(4), Some(2));
+    assert_eq!(c.dropped_items(), 2);
+    assert_eq!(c.slots(), 2);
+    assert_eq!(c.pop(), Ok(3));
+    assert_eq!(p.force_push(5), None);
+    assert_eq!(c.pop(), Ok(4));
+    assert_eq!(c.pop(), Ok(5));
+    assert_eq!(c.pop(), Err(PopError::Empty));
+    assert_eq!(c.dropped_items(), 2);
+}
+
+#[test]
+fn zero_capacity() {
+    let (mut p, c) = RingBuffer::new_overwriting(0);
+    assert_eq!(p.force_push(1), Some(1));
+    assert_eq!(c.dropped_items(), 1);
+    assert!(p.write_chunk_uninit_overwrite(0).is_ok());
+    assert_eq!(
+        p.write_chunk_uninit_overwrite(1).unwrap_err(),
+        ChunkError::TooFewSlots(0)
+    );
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn capacity_overflow() {
+    // The positions would collide with the bit that marks the head as claimed.
+    let _ = RingBuffer::<()>::new_overwriting(usize::MAX / 2);
+}
+
+#[test]
+fn reading() {
+    let (mut p, mut c) = RingBuffer::new_overwriting(2);
+    assert_eq!(p.force_push(1), None);
+    assert_eq!(p.force_push(2), None);
+    assert_eq!(c.peek(), Ok(&1));
+    // The oldest item cannot be discarded while it is referenced:
+    assert_eq!(p.force_push(3), Some(3));
+    assert_eq!(c.dropped_items(), 1);
+    assert_eq!(c.pop(), Ok(1));
+    let chunk = c.read_chunk(1).unwrap();
+    assert_eq!(p.force_push(4), None);
+    assert_eq!(p.force_push(5), Some(5));
+    assert_eq!(
+        p.write_chunk_uninit_overwrite(1).unwrap_err(),
+        ChunkError::TooFewSlots(0)
+    );
+    chunk.commit_all();
+    assert_eq!(p.force_push(6), None);
+    assert_eq!(p.force_push(7), Some(4));
+    assert_eq!(c.pop(), Ok(6));
+    assert_eq!(c.pop(), Ok(7));
+    assert_eq!(c.dropped_items(), 3);
+}
+
+#[test]
+fn stale_tail() {
+    let (mut p, mut c) = RingBuffer::<u32>::new_overwriting(2);
+    p.push(1).unwrap();
+    p.push(2).unwrap();
+    assert_eq!(c.slots(), 2);
+    assert_eq!(p.force_push(3), Some(1));
+    assert_eq!(p.force_push(4), Some(2));
+    assert_eq!(p.force_push(5), Some(3));
+    assert_eq!(c.read_chunk(3).unwrap_err(), ChunkError::TooFewSlots(2));
+    assert_eq!(c.pop(), Ok(4));
+    assert_eq!(c.pop(), Ok(5));
+    assert_eq!(c.pop(), Err(PopError::Empty));
+
+    // The same with `is_empty()`, which moves the cached head without claiming it.
+    let (mut p, mut c) = RingBuffer::<String>::new_overwriting(2);
+    p.push("a".into()).unwrap();
+    p.push("b".into()).unwrap();
+    assert_eq!(c.slots(), 2);
+    for s in ["c", "d", "e"] {
+        assert!(p.force_push(s.into()).is_some());
+    }
+    assert!(!c.is_empty());
+    assert_eq!(c.slots(), 2);
+    let chunk = c.read_chunk(2).unwrap();
+    assert!(chunk.into_iter().eq(["d", "e"]));
+    assert_eq!(c.pop(), Err(PopError::Empty));
+}
+
+#[test]
+fn chunks() {
+    let (mut p, mut c) = RingBuffer::new_overwriting(4);
+    let mut chunk = p.write_chunk_overwrite(3).unwrap();
+    chunk.as_mut_slices().0.copy_from_slice(&[0, 1, 2]);
+    chunk.commit_all();
+    let chunk = p.write_chunk_uninit_overwrite(3).unwrap();
+    assert_eq!(chunk.fill_from_iter(3..), 3);
+    assert_eq!(c.dropped_items(), 2);
+    assert_eq!(
+        p.write_chunk_uninit_overwrite(5).unwrap_err(),
+        ChunkError::TooFewSlots(0)
+    );
+    let chunk = c.read_chunk(4).unwrap();
+    assert_eq!(chunk.into_iter().collect::<Vec<_>>(), [2, 3, 4, 5]);
+}
+
+#[test]
+fn drops() {
+    let (mut p, mut c) = RingBuffer::new_overwriting(3);
+    for i in 0..10 {
+        p.force_push(vec![i]);
+    }
+    let chunk = p.write_chunk_overwrite(2).unwrap();
+    chunk.commit_all();
+    assert_eq!(c.dropped_items(), 9);
+    assert_eq!(c.pop(), Ok(vec![9]));
+    assert_eq!(c.pop(), Ok(vec![]));
+    assert_eq!(c.pop(), Ok(vec![]));
+}
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 10_000;
+    let (mut p, mut c) = RingBuffer::new_overwriting(4);
+    let pop_thread = std::thread::spawn(move || {
+        let mut received = 0;
+        let mut last = None;
+        loop {
+            match c.pop() {
+                Ok(value) => {
+                    let value: Box<usize> = value;
+                    assert!(last < Some(*value));
+                    last = Some(*value);
+                    received += 1;
+                }
+                Err(PopError::Empty) => {}
+                Err(PopError::Closed) => break,
+            }
+        }
+        received + c.dropped_items()
+    });
+    let push_thread = std::thread::spawn(move || {
+        for i in 0..COUNT {
+            p.force_push(Box::new(i));
+        }
+    });
+    push_thread.join().unwrap();
+    assert_eq!(pop_thread.join().unwrap(), COUNT);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn parallel_read() {
+    use std::io::Read;
+
+    const COUNT: usize = 100_000;
+    let (mut p, mut c) = RingBuffer::new_overwriting(4);
+    let read_thread = std::thread::spawn(move || {
+        let mut received = 0;
+        // The buffer is larger than the capacity, so fewer bytes than requested are read.
+        let mut buf = [0; 8];
+        loop {
+            match c.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => received += n,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => panic!("{}", e),
+            }
+        }
+        received + c.dropped_items()
+    });
+    for i in 0..COUNT {
+        p.force_push(i as u8);
+    }
+    drop(p);
+    assert_eq!(read_thread.join().unwrap(), COUNT);
+}
+
+#[test]
+#[should_panic(expected = "overwriting is not enabled")]
+fn not_enabled() {
+    let (mut p, _c) = RingBuffer::new(1);
+    p.force_push(1);
+}
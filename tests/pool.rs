@@ -0,0 +1,91 @@
+#![cfg(feature = "alloc")]
+
+use rtrb::pool::{Pool, Stats};
+use rtrb::PushError;
+
+#[test]
+fn acquire_and_release() {
+    let mut next = 0;
+    let (mut m, mut p) = Pool::new(3, || {
+        next += 1;
+        next
+    });
+    assert_eq!(p.available(), 3);
+    assert_eq!(p.acquire(), Some(1));
+    assert_eq!(p.acquire(), Some(2));
+    p.release(1).unwrap();
+    assert_eq!(m.released(), 1);
+    assert_eq!(p.available(), 1);
+    assert_eq!(m.refill_with(|x| *x *= 10), 1);
+    assert_eq!(m.released(), 0);
+    assert_eq!(p.acquire(), Some(3));
+    assert_eq!(p.acquire(), Some(10));
+    assert_eq!(p.acquire(), None);
+    assert_eq!(m.refill(), 0);
+}
+
+#[test]
+fn stats() {
+    let (m, mut p) = Pool::new(2, String::new);
+    assert_eq!(
+        p.stats(),
+        Stats {
+            acquired: 0,
+            exhausted: 0,
+            min_available: 2,
+        }
+    );
+    let a = p.acquire().unwrap();
+    assert_eq!(m.stats().min_available, 1);
+    p.release(a).unwrap();
+    let _b = p.acquire().unwrap();
+    assert!(p.acquire().is_none());
+    assert!(p.acquire().is_none());
+    assert_eq!(
+        m.stats(),
+        Stats {
+            acquired: 2,
+            exhausted: 2,
+            min_available: 0,
+        }
+    );
+}
+
+#[test]
+fn abandoned() {
+    let (m, mut p) = Pool::new(1, || 0);
+    let x = p.acquire().unwrap();
+    drop(m);
+    assert_eq!(p.release(x), Err(PushError::Closed(0)));
+
+    let (mut m, mut p) = Pool::new(1, || 0);
+    let x = p.acquire().unwrap();
+    p.release(x).unwrap();
+    assert!(!m.is_abandoned());
+    drop(p);
+    assert!(m.is_abandoned());
+    assert_eq!(m.refill(), 0);
+}
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 1000;
+    let (mut m, mut p) = Pool::new(4, || vec![0u8; 16]);
+    let t = std::thread::spawn(move || {
+        let mut acquired = 0;
+        while acquired < COUNT {
+            if let Some(v) = p.acquire() {
+                assert_eq!(v, [0; 16]);
+                acquired += 1;
+                p.release(v).unwrap();
+            }
+        }
+        p.stats()
+    });
+    while !m.is_abandoned() {
+        m.refill();
+    }
+    let stats = t.join().unwrap();
+    assert_eq!(stats.acquired, COUNT);
+    assert_eq!(m.stats(), stats);
+}
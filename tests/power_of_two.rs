@@ -0,0 +1,70 @@
+#![cfg(feature = "alloc")]
+
+use std::rc::Rc;
+
+use rtrb::{chunks::ChunkError, RingBuffer};
+
+#[test]
+fn wrap_around() {
+    for &capacity in &[1, 2, 4, 8] {
+        let (mut p, mut c) = RingBuffer::new_power_of_two(capacity);
+        assert_eq!(p.buffer().capacity(), capacity);
+        for i in 0..10 * capacity {
+            p.push(i).unwrap();
+            if i % 3 == 0 {
+                while p.push(i).is_ok() {}
+                assert!(p.is_full());
+                assert_eq!(c.slots(), capacity);
+                while c.pop().is_ok() {}
+                assert_eq!(p.slots(), capacity);
+            } else {
+                assert_eq!(c.pop(), Ok(i));
+            }
+        }
+    }
+}
+
+#[test]
+fn chunks() {
+    let (mut p, mut c) = RingBuffer::new_power_of_two(8);
+    for round in 0..10 {
+        let n = round % 8 + 1;
+        let chunk = p.write_chunk_uninit(n).unwrap();
+        assert_eq!(chunk.fill_from_iter(0..), n);
+        assert_eq!(
+            p.write_chunk_uninit(9 - n).unwrap_err(),
+            ChunkError::TooFewSlots(8 - n)
+        );
+        let chunk = c.read_chunk(n).unwrap();
+        let (first, second) = chunk.as_slices();
+        assert_eq!(first.len() + second.len(), n);
+        assert!(chunk.into_iter().eq(0..n));
+    }
+}
+
+#[test]
+fn drop_remaining_items() {
+    let item = Rc::new(());
+    let (mut p, mut c) = RingBuffer::new_power_of_two(4);
+    for _ in 0..6 {
+        p.push(item.clone()).unwrap();
+        assert!(c.pop().is_ok());
+    }
+    p.push(item.clone()).unwrap();
+    p.push(item.clone()).unwrap();
+    assert_eq!(Rc::strong_count(&item), 3);
+    drop((p, c));
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+#[should_panic(expected = "capacity must be a power of two")]
+fn not_power_of_two() {
+    let _ = RingBuffer::<u8>::new_power_of_two(3);
+}
+
+#[test]
+#[should_panic(expected = "capacity must be a power of two")]
+fn zero_capacity() {
+    let _ = RingBuffer::<u8>::new_power_of_two(0);
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "std")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rtrb::reclaim::{set_realtime_thread, Garbage, Reclaimer};
+use rtrb::RingBuffer;
+
+struct DropCounter(Arc<AtomicUsize>);
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Runs `f` on a new thread that is marked as realtime thread.
+fn realtime(f: impl FnOnce() + Send + 'static) {
+    std::thread::spawn(move || {
+        set_realtime_thread(true);
+        f();
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn deferred_drop() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let reclaimer = Reclaimer::new();
+    let (mut p, c) = RingBuffer::new(3);
+    reclaimer.register(&p);
+    p.push(DropCounter(dropped.clone())).unwrap();
+    p.push(DropCounter(dropped.clone())).unwrap();
+    realtime(move || drop(p));
+    assert_eq!(reclaimer.pending(), 0);
+    realtime(move || drop(c));
+    assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    assert_eq!(reclaimer.pending(), 1);
+    assert_eq!(reclaimer.reclaim(), 1);
+    assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    assert_eq!(reclaimer.pending(), 0);
+    assert_eq!(reclaimer.reclaim(), 0);
+}
+
+#[test]
+fn non_realtime_drop() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let reclaimer = Reclaimer::new();
+    let (mut p, c) = RingBuffer::new(1);
+    reclaimer.register_consumer(&c);
+    p.push(DropCounter(dropped.clone())).unwrap();
+    realtime(move || drop(p));
+    // The last handle is dropped on a thread that's not marked as realtime.
+    drop(c);
+    assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    assert_eq!(reclaimer.pending(), 0);
+    assert_eq!(reclaimer.reclaim(), 0);
+}
+
+#[test]
+fn multiple_buffers() {
+    let reclaimer = Reclaimer::new();
+    let (p1, c1) = RingBuffer::<String>::new(1);
+    let (p2, c2) = RingBuffer::<f32>::new(2);
+    let (p3, c3) = RingBuffer::<()>::new(3);
+    let (p4, c4) = RingBuffer::<u8>::new(4);
+    reclaimer.register(&p1);
+    reclaimer.register_consumer(&c2);
+    reclaimer.clone().register(&p3);
+    // Registering twice has no effect.
+    reclaimer.register(&p3);
+    realtime(move || drop((p1, c1, p3, c3, p4, c4)));
+    assert_eq!(reclaimer.pending(), 2);
+    assert_eq!(reclaimer.reclaim(), 2);
+    realtime(move || drop((p2, c2)));
+    assert_eq!(reclaimer.reclaim(), 1);
+    assert_eq!(reclaimer.reclaim(), 0);
+}
+
+#[test]
+fn callback() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let garbage = Arc::new(Mutex::new(Vec::<Garbage>::new()));
+    let reclaimer = Reclaimer::with_callback({
+        let garbage = garbage.clone();
+        move |g| garbage.lock().unwrap().push(g)
+    });
+    let (mut p, c) = RingBuffer::new(2);
+    reclaimer.register(&p);
+    p.push(DropCounter(dropped.clone())).unwrap();
+    realtime(move || drop((p, c)));
+    assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    assert_eq!(garbage.lock().unwrap().len(), 1);
+    assert_eq!(reclaimer.pending(), 1);
+    garbage.lock().unwrap().clear();
+    assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    assert_eq!(reclaimer.pending(), 0);
+}
+
+#[test]
+fn spawn() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let reclaimer = Reclaimer::spawn(Duration::from_millis(1));
+    let (mut p, c) = RingBuffer::new(1);
+    reclaimer.register(&p);
+    p.push(DropCounter(dropped.clone())).unwrap();
+    realtime(move || drop((p, c)));
+    while dropped.load(Ordering::Relaxed) == 0 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(reclaimer.pending(), 0);
+}
@@ -0,0 +1,77 @@
+#![cfg(feature = "alloc")]
+
+use rtrb::{PopError, PushError, RingBuffer};
+
+#[test]
+fn outstanding_items() {
+    let (mut p, mut c) = RingBuffer::new_with_return(2);
+    assert_eq!(p.capacity(), 2);
+    p.push(1).unwrap();
+    p.push(2).unwrap();
+    assert!(p.is_full());
+    assert_eq!(c.pop(), Ok(1));
+    assert_eq!(c.pop(), Ok(2));
+    // Popped items are still outstanding until they are reclaimed.
+    assert_eq!(p.push(3), Err(PushError::Full(3)));
+    c.recycle(1).unwrap();
+    assert_eq!(p.reclaimable(), 1);
+    assert_eq!(p.slots(), 0);
+    assert_eq!(p.reclaim(), Ok(1));
+    assert_eq!(p.slots(), 1);
+    assert_eq!(p.reclaim(), Err(PopError::Empty));
+    p.push(3).unwrap();
+    assert_eq!(p.push(4), Err(PushError::Full(4)));
+}
+
+#[test]
+fn recycling_never_fails() {
+    let (mut p, mut c) = RingBuffer::new_with_return(3);
+    for i in 0..100 {
+        while p.push(i).is_ok() {}
+        while let Ok(value) = c.pop() {
+            c.recycle(value).unwrap();
+        }
+        if i % 3 == 0 {
+            p.reclaim().unwrap();
+        }
+    }
+    while p.reclaim().is_ok() {}
+    assert_eq!(p.slots(), 3);
+}
+
+#[test]
+fn recycle_too_many() {
+    let (mut p, mut c) = RingBuffer::new_with_return(2);
+    assert_eq!(c.recycle(0), Err(PushError::Full(0)));
+    p.push(1).unwrap();
+    assert_eq!(c.recycle(0), Err(PushError::Full(0)));
+    assert_eq!(c.pop(), Ok(1));
+    // A different item can be recycled in place of the popped one.
+    c.recycle(10).unwrap();
+    assert_eq!(c.recycle(1), Err(PushError::Full(1)));
+    assert_eq!(p.reclaim(), Ok(10));
+    assert_eq!(p.reclaim(), Err(PopError::Empty));
+    assert_eq!(p.slots(), 2);
+}
+
+#[test]
+fn abandoned() {
+    let (mut p, mut c) = RingBuffer::new_with_return(2);
+    p.push(String::from("a")).unwrap();
+    let item = c.pop().unwrap();
+    c.recycle(item).unwrap();
+    assert!(!p.is_abandoned());
+    drop(c);
+    assert!(p.is_abandoned());
+    assert_eq!(p.reclaim(), Ok(String::from("a")));
+    assert_eq!(p.reclaim(), Err(PopError::Closed));
+    assert_eq!(
+        p.push(String::from("b")),
+        Err(PushError::Closed(String::from("b")))
+    );
+
+    let (p, mut c) = RingBuffer::new_with_return(1);
+    drop(p);
+    assert!(c.is_abandoned());
+    assert_eq!(c.recycle(5), Err(PushError::Closed(5)));
+}
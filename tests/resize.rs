@@ -0,0 +1,87 @@
+#![cfg(feature = "alloc")]
+
+use rtrb::chunks::ChunkError;
+use rtrb::resize::Successor;
+use rtrb::{PopError, RingBuffer};
+
+#[test]
+fn switch() {
+    let (mut p, mut c) = RingBuffer::new_resizable(2);
+    assert_eq!(p.capacity(), 2);
+    p.push(1).unwrap();
+    p.resize(3);
+    p.push(2).unwrap();
+    p.resize(1);
+    p.push(3).unwrap();
+    assert!(p.is_full());
+    assert_eq!(p.capacity(), 1);
+    assert_eq!(c.capacity(), 2);
+    assert_eq!(c.slots(), 1);
+    assert_eq!(c.pop(), Ok(1));
+    assert_eq!(c.peek(), Ok(&2));
+    assert_eq!(c.capacity(), 3);
+    assert_eq!(c.pop(), Ok(2));
+    assert_eq!(c.pop(), Ok(3));
+    assert_eq!(c.capacity(), 1);
+    assert_eq!(c.pop(), Err(PopError::Empty));
+    drop(p);
+    assert_eq!(c.pop(), Err(PopError::Closed));
+}
+
+#[test]
+fn chunks() {
+    let (mut p, mut c) = RingBuffer::new_resizable(4);
+    let chunk = p.write_chunk_uninit(2).unwrap();
+    assert_eq!(chunk.fill_from_iter(0..), 2);
+    p.switch_to(Successor::new(8)).unwrap();
+    let chunk = p.write_chunk_uninit(8).unwrap();
+    assert_eq!(chunk.fill_from_iter(2..), 8);
+    // A chunk doesn't span multiple ring buffers.
+    assert_eq!(c.read_chunk(3).unwrap_err(), ChunkError::TooFewSlots(2));
+    assert!(c.read_chunk(2).unwrap().into_iter().eq(0..2));
+    assert!(c.read_chunk(8).unwrap().into_iter().eq(2..10));
+    assert!(c.is_empty());
+}
+
+#[test]
+fn abandoned() {
+    let (mut p, c) = RingBuffer::new_resizable(1);
+    drop(c);
+    assert!(p.is_abandoned());
+    p.resize(2);
+    assert!(p.is_abandoned());
+    assert_eq!(p.capacity(), 1);
+    assert!(p.push(1).is_err());
+    // The successor is returned instead of being deallocated.
+    let successor = p.switch_to(Successor::new(3)).unwrap_err();
+    assert_eq!(successor.capacity(), 3);
+    assert_eq!(p.capacity(), 1);
+}
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 1_000;
+    let (mut p, mut c) = RingBuffer::new_resizable(1);
+    let pop_thread = std::thread::spawn(move || {
+        let mut expected = 0;
+        loop {
+            match c.pop() {
+                Ok(value) => {
+                    assert_eq!(value, expected);
+                    expected += 1;
+                }
+                Err(PopError::Empty) => {}
+                Err(PopError::Closed) => break,
+            }
+        }
+        expected
+    });
+    for i in 0..COUNT {
+        if i % 10 == 0 {
+            p.resize(i % 7 + 1);
+        }
+        while p.push(i).is_err() {}
+    }
+    drop(p);
+    assert_eq!(pop_thread.join().unwrap(), COUNT);
+}
@@ -0,0 +1,137 @@
+#![cfg(all(feature = "shm", target_os = "linux"))]
+
+use std::io;
+
+use rtrb::shm::SharedRingBuffer;
+use rtrb::{PopError, PushError};
+
+#[test]
+fn push_and_pop() {
+    let buffer = SharedRingBuffer::<u32>::new(3).unwrap();
+    assert_eq!(buffer.capacity(), 3);
+    let other = unsafe { SharedRingBuffer::<u32>::from_raw_fd(libc::dup(buffer.as_raw_fd())) };
+    let mut p = buffer.into_producer().unwrap();
+    let mut c = other.unwrap().into_consumer().unwrap();
+    for i in 0..10 {
+        assert_eq!(p.push(i), Ok(()));
+        assert_eq!(p.push_slice(&[i, i]), 2);
+        assert_eq!(p.push(99), Err(PushError::Full(99)));
+        assert!(p.is_full());
+        let mut buf = [0; 4];
+        assert_eq!(c.pop_slice(&mut buf), 3);
+        assert_eq!(buf, [i, i, i, 0]);
+        assert_eq!(c.pop(), Err(PopError::Empty));
+        assert!(c.is_empty());
+    }
+    p.close();
+    assert_eq!(p.push(1), Err(PushError::Closed(1)));
+    assert_eq!(c.pop(), Err(PopError::Closed));
+    drop(p);
+    assert!(c.is_abandoned());
+}
+
+#[test]
+fn fork() {
+    const COUNT: u64 = 10_000;
+    let buffer = SharedRingBuffer::<u64>::new(16).unwrap();
+    match unsafe { libc::fork() } {
+        -1 => panic!("{}", io::Error::last_os_error()),
+        0 => {
+            // Child process: panics cannot be reported, the exit code is used instead.
+            let code = match buffer.into_producer() {
+                Ok(mut p) => {
+                    for i in 0..COUNT {
+                        while p.push(i).is_err() {}
+                    }
+                    0
+                }
+                Err(_) => 1,
+            };
+            unsafe { libc::_exit(code) };
+        }
+        pid => {
+            let mut c = buffer.into_consumer().unwrap();
+            let mut expected = 0;
+            loop {
+                match c.pop() {
+                    Ok(value) => {
+                        assert_eq!(value, expected);
+                        expected += 1;
+                    }
+                    Err(PopError::Empty) => {}
+                    Err(PopError::Closed) => break,
+                }
+            }
+            assert_eq!(expected, COUNT);
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+            assert!(libc::WIFEXITED(status));
+            assert_eq!(libc::WEXITSTATUS(status), 0);
+        }
+    }
+}
+
+#[test]
+fn attach_twice() {
+    let buffer = SharedRingBuffer::<u8>::new(1).unwrap();
+    let other = unsafe { SharedRingBuffer::<u8>::from_raw_fd(libc::dup(buffer.as_raw_fd())) };
+    let _p = buffer.into_producer().unwrap();
+    let err = other.unwrap().into_producer().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+}
+
+#[test]
+fn wrong_type() {
+    let buffer = SharedRingBuffer::<u16>::new(4).unwrap();
+    let err =
+        unsafe { SharedRingBuffer::<u32>::from_raw_fd(libc::dup(buffer.as_raw_fd())) }.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn not_shared_memory() {
+    let fd = unsafe { libc::memfd_create(b"empty\0".as_ptr().cast(), 0) };
+    assert!(fd >= 0);
+    let err = unsafe { SharedRingBuffer::<u8>::from_raw_fd(fd) }.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+/// Simulates a misbehaving process by overwriting a position in the header.
+#[cfg(target_arch = "x86_64")]
+fn corrupt(fd: std::os::unix::io::RawFd, offset: libc::off_t, pos: usize) {
+    let size = std::mem::size_of::<usize>();
+    let written = unsafe { libc::pwrite(fd, (&pos as *const usize).cast(), size, offset) };
+    assert_eq!(written, size as isize);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn invalid_positions() {
+    // Both positions are cache-padded (to 128 bytes on x86_64).
+    const HEAD: libc::off_t = 128;
+    const TAIL: libc::off_t = 256;
+
+    let buffer = SharedRingBuffer::<u32>::new(4).unwrap();
+    let fd = buffer.as_raw_fd();
+    let other = unsafe { SharedRingBuffer::<u32>::from_raw_fd(libc::dup(fd)) }.unwrap();
+    let mut p = buffer.into_producer().unwrap();
+    let mut c = other.into_consumer().unwrap();
+    assert_eq!(p.push_slice(&[1, 2]), 2);
+    assert_eq!(c.pop(), Ok(1));
+    corrupt(fd, TAIL, 8);
+    assert_eq!(c.slots(), 0);
+    assert_eq!(c.pop(), Err(PopError::Closed));
+    assert_eq!(p.push(3), Err(PushError::Closed(3)));
+
+    let buffer = SharedRingBuffer::<u32>::new(4).unwrap();
+    let fd = buffer.as_raw_fd();
+    let other = unsafe { SharedRingBuffer::<u32>::from_raw_fd(libc::dup(fd)) }.unwrap();
+    let mut p = buffer.into_producer().unwrap();
+    assert_eq!(p.push(1), Ok(()));
+    // More than `capacity` slots would be in use.
+    corrupt(fd, HEAD, 2);
+    assert_eq!(p.slots(), 0);
+    assert_eq!(p.push_slice(&[2, 3]), 0);
+    let err = other.into_consumer().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
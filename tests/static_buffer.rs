@@ -0,0 +1,187 @@
+use rtrb::chunks::ChunkError;
+use rtrb::{PopError, PushError, StaticRingBuffer};
+
+#[test]
+fn push_and_pop() {
+    let mut buffer = StaticRingBuffer::<_, 2>::new();
+    assert_eq!(buffer.capacity(), 2);
+    let (mut p, mut c) = buffer.split();
+    assert_eq!(p.buffer().capacity(), 2);
+    assert_eq!(p.buffer(), c.buffer());
+    for i in 0..10 {
+        assert_eq!(p.push(i), Ok(()));
+        assert_eq!(c.peek(), Ok(&i));
+        assert_eq!(c.pop(), Ok(i));
+        assert_eq!(c.pop(), Err(PopError::Empty));
+    }
+    assert_eq!(p.push(10), Ok(()));
+    assert_eq!(p.push(11), Ok(()));
+    assert_eq!(p.push(12), Err(PushError::Full(12)));
+    drop(p);
+    assert_eq!(c.pop(), Ok(10));
+    assert_eq!(c.pop(), Ok(11));
+    assert_eq!(c.pop(), Err(PopError::Closed));
+}
+
+#[test]
+fn chunks() {
+    let mut buffer = StaticRingBuffer::<_, 4>::new();
+    let (mut p, mut c) = buffer.split();
+    assert_eq!(p.write_chunk_uninit(3).unwrap().fill_from_iter(0..), 3);
+    assert_eq!(p.write_chunk(2).unwrap_err(), ChunkError::TooFewSlots(1));
+    let chunk = c.read_chunk(2).unwrap();
+    assert_eq!(chunk.into_iter().collect::<Vec<_>>(), [0, 1]);
+    let mut chunk = p.write_chunk(3).unwrap();
+    chunk.as_mut_slices().0.copy_from_slice(&[3]);
+    chunk.as_mut_slices().1.copy_from_slice(&[4, 5]);
+    chunk.commit_all();
+    let chunk = c.read_chunk(4).unwrap();
+    assert_eq!(chunk.as_slices(), (&[2, 3][..], &[4, 5][..]));
+    chunk.commit_all();
+    assert!(c.is_empty());
+}
+
+#[test]
+fn split_again() {
+    let mut buffer = StaticRingBuffer::<_, 3>::new();
+    let (mut p, mut c) = buffer.split();
+    assert_eq!(p.push(1), Ok(()));
+    assert_eq!(p.push(2), Ok(()));
+    assert_eq!(c.pop(), Ok(1));
+    c.close();
+    assert_eq!(p.push(3), Err(PushError::Closed(3)));
+    drop((p, c));
+
+    let (mut p, mut c) = buffer.split();
+    assert!(!p.is_closed());
+    assert_eq!(p.push(3), Ok(()));
+    assert_eq!(c.pop(), Ok(2));
+    assert_eq!(c.pop(), Ok(3));
+}
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 10_000;
+    let mut buffer = StaticRingBuffer::<_, 4>::new();
+    let (mut p, mut c) = buffer.split();
+    std::thread::scope(|s| {
+        s.spawn(move || {
+            for i in 0..COUNT {
+                loop {
+                    match c.pop() {
+                        Ok(x) => {
+                            assert_eq!(x, i);
+                            break;
+                        }
+                        Err(PopError::Empty) => std::thread::yield_now(),
+                        Err(PopError::Closed) => unreachable!(),
+                    }
+                }
+            }
+        });
+        for i in 0..COUNT {
+            while p.push(i).is_err() {
+                std::thread::yield_now();
+            }
+        }
+    });
+}
+
+#[test]
+fn drops() {
+    use std::rc::Rc;
+
+    let item = Rc::new(());
+    {
+        let mut buffer = StaticRingBuffer::<_, 3>::new();
+        let (mut p, mut c) = buffer.split();
+        for _ in 0..5 {
+            let _ = p.push(item.clone());
+        }
+        assert_eq!(Rc::strong_count(&item), 4);
+        drop(c.pop());
+        assert_eq!(Rc::strong_count(&item), 3);
+    }
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+fn scoped() {
+    use std::mem::MaybeUninit;
+
+    let mut slots: Vec<MaybeUninit<String>> = (0..3).map(|_| MaybeUninit::uninit()).collect();
+    let mut buffer = rtrb::RingBuffer::scoped(&mut slots);
+    assert_eq!(buffer.capacity(), 3);
+    let (mut p, mut c) = buffer.split();
+    assert_eq!(p.push("one".to_string()), Ok(()));
+    assert_eq!(p.push("two".to_string()), Ok(()));
+    assert_eq!(c.pop().as_deref(), Ok("one"));
+    drop((p, c));
+    let (_, mut c) = buffer.split();
+    assert_eq!(c.pop().as_deref(), Ok("two"));
+    // The producer has already been dropped:
+    assert_eq!(c.pop(), Err(PopError::Closed));
+}
+
+#[test]
+fn scoped_drops() {
+    use std::mem::MaybeUninit;
+    use std::rc::Rc;
+
+    let item = Rc::new(());
+    let mut slots: Vec<MaybeUninit<Rc<()>>> = (0..2).map(|_| MaybeUninit::uninit()).collect();
+    {
+        let mut buffer = rtrb::RingBuffer::scoped(&mut slots);
+        let (mut p, _c) = buffer.split();
+        p.push(item.clone()).unwrap();
+        p.push(item.clone()).unwrap();
+        assert_eq!(Rc::strong_count(&item), 3);
+    }
+    assert_eq!(Rc::strong_count(&item), 1);
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn scoped_capacity_overflow() {
+    use std::mem::MaybeUninit;
+    use std::ptr::NonNull;
+
+    // Safety: A dangling pointer is fine for zero-sized types.
+    let slots: &mut [MaybeUninit<()>] =
+        unsafe { std::slice::from_raw_parts_mut(NonNull::dangling().as_ptr(), usize::MAX / 2 + 1) };
+    let _ = rtrb::RingBuffer::scoped(slots);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn scoped_read_and_write() {
+    use std::io::{Read, Write};
+
+    let mut slots = [std::mem::MaybeUninit::uninit(); 5];
+    let mut buffer = rtrb::RingBuffer::scoped(&mut slots);
+    let (mut p, mut c) = buffer.split();
+    let input: Vec<u8> = (0..=255).collect();
+    let output = std::thread::scope(|s| {
+        s.spawn(move || {
+            let mut rest = &input[..];
+            while !rest.is_empty() {
+                match p.write(rest) {
+                    Ok(n) => rest = &rest[n..],
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                    Err(e) => panic!("{}", e),
+                }
+            }
+        });
+        let mut output = Vec::new();
+        let mut buf = [0; 3];
+        loop {
+            match c.read(&mut buf) {
+                Ok(0) => break output,
+                Ok(n) => output.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                Err(e) => panic!("{}", e),
+            }
+        }
+    });
+    assert_eq!(output, (0..=255).collect::<Vec<u8>>());
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "alloc")]
+
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use rtrb::{PushError, RingBuffer, Storage};
+
+fn boxed_slice<T>(capacity: usize) -> Box<[MaybeUninit<T>]> {
+    (0..capacity).map(|_| MaybeUninit::uninit()).collect()
+}
+
+#[test]
+fn boxed() {
+    let (mut p, mut c) = RingBuffer::with_storage(boxed_slice(2));
+    assert_eq!(p.buffer().capacity(), 2);
+    for i in 0..10 {
+        assert_eq!(p.push(i), Ok(()));
+        assert_eq!(c.pop(), Ok(i));
+    }
+    assert_eq!(p.push(10), Ok(()));
+    assert_eq!(p.push(11), Ok(()));
+    assert_eq!(p.push(12), Err(PushError::Full(12)));
+    let chunk = c.read_chunk(2).unwrap();
+    assert!(chunk.into_iter().eq([10, 11]));
+}
+
+#[test]
+fn leaked() {
+    let slots: &'static mut [MaybeUninit<String>] = Box::leak(boxed_slice(3));
+    let (mut p, mut c) = RingBuffer::with_storage(slots);
+    assert_eq!(p.push("hello".to_string()), Ok(()));
+    assert_eq!(c.pop().as_deref(), Ok("hello"));
+}
+
+#[test]
+fn zero_capacity() {
+    let (mut p, c) = RingBuffer::with_storage(boxed_slice(0));
+    assert_eq!(p.push(1), Err(PushError::Full(1)));
+    assert!(c.is_empty());
+}
+
+/// Keeps track of when the storage and the items are dropped.
+struct Tracked {
+    slots: Box<[MaybeUninit<Item>]>,
+    events: Arc<AtomicUsize>,
+}
+
+unsafe impl Storage<Item> for Tracked {
+    fn as_mut_ptr(&mut self) -> *mut Item {
+        self.slots.as_mut_ptr().cast()
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        // All items must have been dropped before.
+        assert_eq!(self.events.load(Ordering::Relaxed), 2);
+        self.events.store(100, Ordering::Relaxed);
+    }
+}
+
+struct Item(Arc<AtomicUsize>);
+
+impl Drop for Item {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn drop_order() {
+    let events = Arc::new(AtomicUsize::new(0));
+    let storage = Tracked {
+        slots: boxed_slice(4),
+        events: events.clone(),
+    };
+    let (mut p, c) = RingBuffer::with_storage(storage);
+    for _ in 0..2 {
+        assert!(p.push(Item(events.clone())).is_ok());
+    }
+    drop(p);
+    assert_eq!(events.load(Ordering::Relaxed), 0);
+    drop(c);
+    assert_eq!(events.load(Ordering::Relaxed), 100);
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "tokio")]
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use rtrb::{Blocking, RingBuffer};
+
+#[tokio::test]
+async fn copy() {
+    let (mut p, mut c) = RingBuffer::with_blocking(3, Blocking::Consumer);
+    let push_thread = std::thread::spawn(move || {
+        for i in 0..100 {
+            while p.push(i).is_err() {}
+        }
+    });
+    let mut v = Vec::new();
+    assert_eq!(tokio::io::copy(&mut c, &mut v).await.unwrap(), 100);
+    push_thread.join().unwrap();
+    assert_eq!(v, (0..100).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn write_and_read() {
+    let (mut p, mut c) = RingBuffer::with_blocking(2, Blocking::Both);
+    let write_task = tokio::spawn(async move {
+        p.write_all(&[10, 11, 12, 13, 14]).await.unwrap();
+    });
+    let mut v = Vec::new();
+    assert_eq!(c.read_to_end(&mut v).await.unwrap(), 5);
+    write_task.await.unwrap();
+    assert_eq!(v, [10, 11, 12, 13, 14]);
+}
+
+#[tokio::test]
+async fn broken_pipe() {
+    let (mut p, c) = RingBuffer::with_blocking(2, Blocking::Producer);
+    assert_eq!(p.write(&[10, 11, 12]).await.unwrap(), 2);
+    drop(c);
+    assert_eq!(
+        p.write(&[13]).await.unwrap_err().kind(),
+        std::io::ErrorKind::BrokenPipe
+    );
+}
+
+#[tokio::test]
+#[should_panic(expected = "blocking is not enabled for the consumer")]
+async fn read_not_enabled() {
+    let (_p, mut c) = RingBuffer::new(1);
+    let _ = c.read_u8().await;
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "alloc")]
+
+use rtrb::chunks::ChunkError;
+use rtrb::{PopError, PushError, RingBuffer};
+
+#[test]
+fn push_and_pop() {
+    let (mut p, mut c) = RingBuffer::new_unbounded(3);
+    assert_eq!(p.segment_capacity(), 3);
+    for i in 0..10 {
+        assert_eq!(p.push(i), Ok(()));
+    }
+    assert_eq!(c.slots(), 3);
+    assert_eq!(c.peek(), Ok(&0));
+    for i in 0..10 {
+        assert_eq!(c.pop(), Ok(i));
+    }
+    assert_eq!(c.pop(), Err(PopError::Empty));
+    assert!(c.is_empty());
+    // Drained segments are reused.
+    for i in 0..10 {
+        assert_eq!(p.push(i), Ok(()));
+    }
+    assert!(!c.is_abandoned());
+    drop(p);
+    assert!(c.is_abandoned());
+    for i in 0..10 {
+        assert_eq!(c.pop(), Ok(i));
+    }
+    assert_eq!(c.pop(), Err(PopError::Closed));
+}
+
+#[test]
+fn chunks() {
+    let (mut p, mut c) = RingBuffer::new_unbounded(4);
+    assert_eq!(
+        p.write_chunk_uninit(5).unwrap_err(),
+        ChunkError::TooFewSlots(4)
+    );
+    assert_eq!(p.write_chunk_uninit(3).unwrap().fill_from_iter(0..), 3);
+    // This continues with the next segment.
+    assert_eq!(p.write_chunk_uninit(2).unwrap().fill_from_iter(3..), 2);
+    assert_eq!(c.read_chunk(4).unwrap_err(), ChunkError::TooFewSlots(3));
+    assert!(c.read_chunk(3).unwrap().into_iter().eq(0..3));
+    assert!(c.read_chunk(2).unwrap().into_iter().eq(3..5));
+}
+
+#[test]
+fn abandoned() {
+    let (mut p, c) = RingBuffer::new_unbounded(1);
+    assert_eq!(p.push(1), Ok(()));
+    drop(c);
+    assert!(p.is_abandoned());
+    assert_eq!(p.push(2), Err(PushError::Closed(2)));
+}
+
+#[test]
+fn drops() {
+    let item = std::rc::Rc::new(());
+    let (mut p, mut c) = RingBuffer::new_unbounded(2);
+    for _ in 0..5 {
+        p.push(item.clone()).unwrap();
+    }
+    assert!(c.pop().is_ok());
+    assert_eq!(std::rc::Rc::strong_count(&item), 5);
+    drop((p, c));
+    assert_eq!(std::rc::Rc::strong_count(&item), 1);
+}
+
+#[test]
+#[should_panic(expected = "segment capacity must not be zero")]
+fn zero_capacity() {
+    let _ = RingBuffer::<i32>::new_unbounded(0);
+}
+
+#[test]
+fn parallel() {
+    const COUNT: usize = 10_000;
+    let (mut p, mut c) = RingBuffer::new_unbounded(16);
+    let pop_thread = std::thread::spawn(move || {
+        let mut expected = 0;
+        loop {
+            match c.pop() {
+                Ok(value) => {
+                    assert_eq!(value, expected);
+                    expected += 1;
+                }
+                Err(PopError::Empty) => {}
+                Err(PopError::Closed) => break,
+            }
+        }
+        expected
+    });
+    for i in 0..COUNT {
+        p.push(i).unwrap();
+    }
+    drop(p);
+    assert_eq!(pop_thread.join().unwrap(), COUNT);
+}
@@ -72,3 +72,48 @@ fn read_error() {
         std::io::ErrorKind::WouldBlock
     );
 }
+
+#[test]
+fn read_end_of_stream() {
+    let (mut p, mut c) = RingBuffer::new(2);
+    assert_eq!(p.push(10), Ok(()));
+    drop(p);
+    let mut buf = [0, 0];
+    assert_eq!(c.read(&mut buf).unwrap(), 1);
+    assert_eq!(buf, [10, 0]);
+    assert_eq!(c.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn read_to_end() {
+    let (mut p, mut c) = RingBuffer::new(3);
+    let write_thread = std::thread::spawn(move || {
+        let mut data = &[10, 11, 12, 13, 14][..];
+        while !data.is_empty() {
+            match p.write(data) {
+                Ok(n) => data = &data[n..],
+                Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock),
+            }
+        }
+    });
+    let mut v = Vec::new();
+    loop {
+        match c.read_to_end(&mut v) {
+            Ok(_) => break,
+            Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock),
+        }
+    }
+    write_thread.join().unwrap();
+    assert_eq!(v, [10, 11, 12, 13, 14]);
+}
+
+#[test]
+fn write_broken_pipe() {
+    let (mut p, c) = RingBuffer::new(2);
+    assert_eq!(p.write(&[10]).unwrap(), 1);
+    drop(c);
+    assert_eq!(
+        p.write(&[11]).unwrap_err().kind(),
+        std::io::ErrorKind::BrokenPipe
+    );
+}